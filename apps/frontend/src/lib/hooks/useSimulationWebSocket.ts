@@ -22,7 +22,20 @@ export interface FoodInit {
   maxAmount: number
 }
 
+export type HazardKind = 'flood' | 'fire' | 'pesticide'
+
+export interface HazardInit {
+  kind: HazardKind
+  x: number
+  y: number
+  radius: number
+}
+
+const HAZARD_KINDS: HazardKind[] = ['flood', 'fire', 'pesticide']
+
 export interface WorldInit {
+  /** protocol version the server speaks */
+  version: number
   simulationId: number
   tick: number
   worldWidth: number
@@ -37,6 +50,7 @@ export interface WorldInit {
   pheromoneCellSize: number
   colonies: ColonyInit[]
   foods: FoodInit[]
+  hazards: HazardInit[]
 }
 
 export interface AntFrame {
@@ -45,7 +59,7 @@ export interface AntFrame {
   y: Uint16Array
   /** quantized heading, 0..255 over 2*PI */
   heading: Uint8Array
-  /** bits 0-1 role, bit 2 carrying */
+  /** bits 0-1 role, bit 2 carrying, bits 3-7 colony slot */
   flags: Uint8Array
   count: number
   tick: number
@@ -53,6 +67,20 @@ export interface AntFrame {
   time: number
 }
 
+export interface ColonyStats {
+  id: number
+  population: number
+  foodStored: number
+  /** non-soldiers currently outside the nest */
+  foragers: number
+  /** food deposited per 1000 ticks */
+  depositRate: number
+  /** mean pickup-to-deposit time, null until a trip completes */
+  avgTripTicks: number | null
+  /** members lost since the colony was founded, by cause */
+  deaths: { starvation: number; oldAge: number; hazard: number }
+}
+
 export interface SimStats {
   tick: number
   antCount: number
@@ -60,6 +88,7 @@ export interface SimStats {
   colonyFood: number
   worldFood: number
   tps: number
+  colonies: ColonyStats[]
 }
 
 /**
@@ -103,7 +132,8 @@ function growFrame(frame: AntFrame, needed: number) {
 
 function decodeInit(view: DataView, bytes: Uint8Array): WorldInit {
   let o = 1 // skip frame type
-  o += 1 // protocol version
+  const version = view.getUint8(o)
+  o += 1
   const simulationId = view.getInt32(o, true)
   o += 4
   const tick = Number(view.getBigUint64(o, true))
@@ -153,10 +183,26 @@ function decodeInit(view: DataView, bytes: Uint8Array): WorldInit {
     o += 20
   }
 
+  const hazards: HazardInit[] = []
+  if (version >= 2) {
+    const hazardCount = view.getUint16(o, true)
+    o += 2
+    for (let i = 0; i < hazardCount; i++) {
+      hazards.push({
+        kind: HAZARD_KINDS[view.getUint8(o)] ?? 'fire',
+        x: view.getFloat32(o + 1, true),
+        y: view.getFloat32(o + 5, true),
+        radius: view.getFloat32(o + 9, true),
+      })
+      o += 13
+    }
+  }
+
   const bitLen = Math.ceil((terrainW * terrainH) / 8)
   const terrainBits = bytes.slice(o, o + bitLen)
 
   return {
+    version,
     simulationId,
     tick,
     worldWidth,
@@ -170,6 +216,7 @@ function decodeInit(view: DataView, bytes: Uint8Array): WorldInit {
     pheromoneCellSize,
     colonies,
     foods,
+    hazards,
   }
 }
 
@@ -192,6 +239,7 @@ export function useSimulationWebSocket(
     foodDirty: false,
   })
 
+  const worldRef = useRef<WorldInit | null>(null)
   const wsRef = useRef<WebSocket | null>(null)
   const reconnectTimer = useRef<ReturnType<typeof setTimeout> | null>(null)
   const attemptsRef = useRef(0)
@@ -213,6 +261,7 @@ export function useSimulationWebSocket(
       buffers.pheromoneDirty = false
       buffers.foodAmounts = new Map(world.foods.map((f) => [f.id, f.amount]))
       buffers.foodDirty = true
+      worldRef.current = world
       setInit(world)
       return
     }
@@ -248,11 +297,41 @@ export function useSimulationWebSocket(
     }
 
     if (frameType === FRAME_PHEROMONE) {
-      const w = view.getUint32(9, true)
-      const h = view.getUint32(13, true)
+      const world = worldRef.current
+      if (!world) return
+      // since version 3 the frame holds a block of cells starting at (x0, y0)
+      let o = 9
+      let x0 = 0
+      let y0 = 0
+      if (world.version >= 3) {
+        x0 = view.getUint32(o, true)
+        y0 = view.getUint32(o + 4, true)
+        o += 8
+      }
+      const w = view.getUint32(o, true)
+      const h = view.getUint32(o + 4, true)
+      o += 8
       const size = w * h
-      buffers.pheromoneFood = bytes.subarray(17, 17 + size)
-      buffers.pheromoneHome = bytes.subarray(17 + size, 17 + size * 2)
+      const food = bytes.subarray(o, o + size)
+      const home = bytes.subarray(o + size, o + size * 2)
+      const gridW = world.pheromoneW
+      const gridH = world.pheromoneH
+      if (x0 === 0 && y0 === 0 && w === gridW && h === gridH) {
+        buffers.pheromoneFood = food
+        buffers.pheromoneHome = home
+      } else {
+        // a viewport block: cells outside it read as empty
+        const fullFood = new Uint8Array(gridW * gridH)
+        const fullHome = new Uint8Array(gridW * gridH)
+        for (let row = 0; row < h && y0 + row < gridH; row++) {
+          const cols = Math.min(w, gridW - x0)
+          const dst = (y0 + row) * gridW + x0
+          fullFood.set(food.subarray(row * w, row * w + cols), dst)
+          fullHome.set(home.subarray(row * w, row * w + cols), dst)
+        }
+        buffers.pheromoneFood = fullFood
+        buffers.pheromoneHome = fullHome
+      }
       buffers.pheromoneDirty = true
       return
     }
@@ -284,7 +363,10 @@ export function useSimulationWebSocket(
             colonyFood: msg.colonyFood,
             worldFood: msg.worldFood,
             tps: msg.tps,
+            colonies: msg.colonies ?? [],
           })
+        } else if (msg.type === 'error') {
+          console.warn('Simulation error:', msg.message)
         }
       } catch {
         // ignore malformed text messages
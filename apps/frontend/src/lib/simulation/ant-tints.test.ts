@@ -0,0 +1,36 @@
+import { describe, expect, it } from 'vitest'
+import { buildAntTints, CARRYING_TINT } from './ant-tints'
+
+const rgb = (tint: number) => [(tint >> 16) & 255, (tint >> 8) & 255, tint & 255]
+
+// largest per-channel difference between two tints, 0..255
+const contrast = (a: number, b: number) =>
+  Math.max(...rgb(a).map((c, i) => Math.abs(c - rgb(b)[i])))
+
+describe('buildAntTints', () => {
+  // hues as the server hands them out for two colonies
+  const tints = buildAntTints([
+    { id: 0, x: 100, y: 100, radius: 20, hue: 30 },
+    { id: 1, x: 300, y: 100, radius: 20, hue: 210 },
+  ])
+  const flags = (slot: number, role: number, carrying = false) =>
+    (slot << 3) | (carrying ? 4 : 0) | role
+
+  it('gives each role a visibly different tint in the two colonies', () => {
+    for (const role of [0, 1, 2]) {
+      expect(contrast(tints[flags(0, role)], tints[flags(1, role)])).toBeGreaterThan(40)
+    }
+  })
+
+  it('keeps roles apart within a colony', () => {
+    for (const slot of [0, 1]) {
+      expect(tints[flags(slot, 1)]).not.toBe(tints[flags(slot, 0)])
+      expect(tints[flags(slot, 2)]).not.toBe(tints[flags(slot, 0)])
+    }
+  })
+
+  it('shows carrying ants the same in every colony', () => {
+    expect(tints[flags(0, 0, true)]).toBe(CARRYING_TINT)
+    expect(tints[flags(1, 2, true)]).toBe(CARRYING_TINT)
+  })
+})
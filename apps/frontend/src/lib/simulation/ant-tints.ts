@@ -0,0 +1,34 @@
+// Ant colours per colony hue and role.
+import type { WorldInit } from '~/lib/hooks/useSimulationWebSocket'
+
+// ant colour per role in the colony's hue: [saturation, lightness]
+const ROLE_SHADES: [number, number][] = [
+  [0.45, 0.42], // worker
+  [0.6, 0.62], // scout
+  [0.55, 0.3], // soldier
+  [0.45, 0.42], // (unused role 3)
+]
+export const CARRYING_TINT = 0x52d273
+
+export function hslToHex(hue: number, s: number, l: number): number {
+  const a = s * Math.min(l, 1 - l)
+  const channel = (n: number) => {
+    const k = (n + hue / 30) % 12
+    return Math.round(255 * (l - a * Math.max(-1, Math.min(k - 3, 9 - k, 1))))
+  }
+  return (channel(0) << 16) | (channel(8) << 8) | channel(4)
+}
+
+/**
+ * Tint lookup indexed by the full flags byte (bits 0-1 role, bit 2 carrying,
+ * bits 3-7 colony slot): role sets the shade, the colony sets the hue.
+ */
+export function buildAntTints(colonies: WorldInit['colonies']): Uint32Array {
+  const tints = new Uint32Array(256)
+  for (let flags = 0; flags < 256; flags++) {
+    const hue = colonies[flags >> 3]?.hue ?? 30
+    const [s, l] = ROLE_SHADES[flags & 3]
+    tints[flags] = flags & 4 ? CARRYING_TINT : hslToHex(hue, s, l)
+  }
+  return tints
+}
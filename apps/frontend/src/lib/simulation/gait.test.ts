@@ -0,0 +1,34 @@
+import { describe, expect, it } from 'vitest'
+import { advanceGait, gaitSway } from './gait'
+
+// gait phase after `frames` render frames of a sixth of a server step each
+function walk(distancePerStep: number, frames: number): number[] {
+  const phases = [0]
+  for (let f = 0; f < frames; f++) {
+    phases.push(advanceGait(phases[phases.length - 1], distancePerStep, 1 / 6))
+  }
+  return phases
+}
+
+describe('advanceGait', () => {
+  it('advances the walk cycle every frame for a moving ant', () => {
+    const phases = walk(3, 6)
+    for (let f = 1; f < phases.length; f++) {
+      expect(phases[f]).toBeGreaterThan(phases[f - 1])
+    }
+    expect(gaitSway(phases[6])).not.toBe(gaitSway(phases[0]))
+  })
+
+  it('holds the frame for a stationary ant', () => {
+    const phases = walk(0, 6)
+    expect(new Set(phases)).toEqual(new Set([0]))
+    expect(gaitSway(phases[6])).toBe(0)
+  })
+
+  it('cycles faster for a faster ant and wraps around', () => {
+    expect(walk(4, 1)[1]).toBeGreaterThan(walk(2, 1)[1])
+    const phase = advanceGait(6, 10, 1)
+    expect(phase).toBeGreaterThanOrEqual(0)
+    expect(phase).toBeLessThan(Math.PI * 2)
+  })
+})
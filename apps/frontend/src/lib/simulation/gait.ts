@@ -0,0 +1,20 @@
+// Walk cycle for the ant sprites: a small side-to-side sway whose rate
+// follows ground speed, so moving ants look alive and stationary ones hold
+// still.
+
+const TAU = Math.PI * 2
+const GAIT_SWAY = 0.12 // radians
+const GAIT_PHASE_PER_UNIT = 0.5 // radians of phase per world unit walked
+
+/**
+ * Gait phase after one render frame, for an ant that moves `distance` world
+ * units per server step and a frame lasting `stepFraction` of a step.
+ */
+export function advanceGait(phase: number, distance: number, stepFraction: number): number {
+  return (phase + distance * stepFraction * GAIT_PHASE_PER_UNIT) % TAU
+}
+
+/** Rotation (radians) added to the ant's heading at a gait phase. */
+export function gaitSway(phase: number): number {
+  return Math.sin(phase) * GAIT_SWAY
+}
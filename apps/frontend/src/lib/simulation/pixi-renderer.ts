@@ -1,10 +1,17 @@
 import type {
+  ColonyStats,
+  HazardKind,
   SimBuffers,
   WorldInit,
 } from '~/lib/hooks/useSimulationWebSocket'
+import { buildAntTints, hslToHex } from '~/lib/simulation/ant-tints'
+import { advanceGait, gaitSway } from '~/lib/simulation/gait'
+import { antScaleForZoom, nestLabelPosition } from '~/lib/simulation/view-scale'
 
 export interface SimRenderer {
   resetWorld(init: WorldInit): void
+  /** Update the population labels above the nests. */
+  setColonyStats(colonies: ColonyStats[]): void
   getFps(): number
   destroy(): void
 }
@@ -12,17 +19,14 @@ export interface SimRenderer {
 const TAU = Math.PI * 2
 const HEADING_TO_RAD = TAU / 256
 
-// tint lookup indexed by flags (bits 0-1 role, bit 2 carrying)
-const TINTS = new Uint32Array([
-  0x9a6a3f, // worker
-  0xd9b25f, // scout
-  0xc25b40, // soldier
-  0x9a6a3f, // (unused role 3)
-  0x52d273, // worker carrying food
-  0x86e09a, // scout carrying food
-  0x52d273, // soldier carrying food
-  0x52d273, // (unused role 3 carrying)
-])
+// nest labels keep this on-screen size at any zoom
+const LABEL_FONT_SIZE = 13
+
+const HAZARD_COLORS: Record<HazardKind, number> = {
+  flood: 0x3a7bd5,
+  fire: 0xe0642a,
+  pesticide: 0x9b5de5,
+}
 
 function hashJitter(i: number): number {
   // deterministic pseudo-random in [0, 1) for per-cell color variation
@@ -134,7 +138,17 @@ export async function createSimRenderer(
       uvs: false,
     },
   })
-  world.addChild(terrainSprite, pheromoneSprite, foodLayer, colonyLayer, antContainer)
+  const labelLayer = new PIXI.Container()
+  world.addChild(
+    terrainSprite,
+    pheromoneSprite,
+    foodLayer,
+    colonyLayer,
+    antContainer,
+    labelLayer,
+  )
+  const colonyLabels = new Map<number, InstanceType<typeof PIXI.Text>>()
+  let lastColonyStats: ColonyStats[] = []
 
   // ── ant texture (points along +x, white for tinting) ────────────────
   const antGfx = new PIXI.Graphics()
@@ -148,6 +162,8 @@ export async function createSimRenderer(
   antGfx.destroy()
 
   const particles: InstanceType<typeof PIXI.Particle>[] = []
+  const gaitPhase: number[] = []
+  let antScale = 1
 
   function ensureParticles(count: number) {
     while (particles.length < count) {
@@ -155,15 +171,19 @@ export async function createSimRenderer(
         texture: antTexture,
         anchorX: 0.5,
         anchorY: 0.5,
+        scaleX: antScale,
+        scaleY: antScale,
       })
       p.alpha = 0
       particles.push(p)
+      gaitPhase.push(0)
       antContainer.addParticle(p)
     }
   }
 
   // ── world state ──────────────────────────────────────────────────────
   let worldMeta: WorldInit | null = null
+  let antTints = buildAntTints([])
   let pheromoneCanvas: HTMLCanvasElement | null = null
   let pheromoneCtx: CanvasRenderingContext2D | null = null
   let pheromoneImage: ImageData | null = null
@@ -187,6 +207,11 @@ export async function createSimRenderer(
   function redrawColonies() {
     if (!worldMeta) return
     colonyLayer.clear()
+    for (const h of worldMeta.hazards) {
+      const color = HAZARD_COLORS[h.kind]
+      colonyLayer.circle(h.x, h.y, h.radius).fill({ color, alpha: 0.22 })
+      colonyLayer.circle(h.x, h.y, h.radius).stroke({ color, alpha: 0.6, width: 2 })
+    }
     for (const c of worldMeta.colonies) {
       colonyLayer.circle(c.x, c.y, c.radius * 1.5).fill({ color: 0x8a6a40, alpha: 0.25 })
       colonyLayer.circle(c.x, c.y, c.radius).fill(0x77552e)
@@ -195,6 +220,50 @@ export async function createSimRenderer(
     }
   }
 
+  function rebuildColonyLabels() {
+    for (const label of colonyLabels.values()) label.destroy()
+    colonyLabels.clear()
+    if (!worldMeta) return
+    for (const c of worldMeta.colonies) {
+      const label = new PIXI.Text({
+        text: `Colony ${c.id}`,
+        style: {
+          fontFamily: 'ui-monospace, monospace',
+          fontSize: LABEL_FONT_SIZE,
+          fill: hslToHex(c.hue, 0.6, 0.75),
+          stroke: { color: 0x000000, width: 3 },
+        },
+        resolution: 2,
+      })
+      label.anchor.set(0.5, 1)
+      labelLayer.addChild(label)
+      colonyLabels.set(c.id, label)
+    }
+    updateLabelPlacement()
+    setColonyStats(lastColonyStats)
+  }
+
+  // labels are counter-scaled so they read the same at any zoom
+  function updateLabelPlacement() {
+    if (!worldMeta) return
+    const inv = 1 / world.scale.x
+    for (const c of worldMeta.colonies) {
+      const label = colonyLabels.get(c.id)
+      if (!label) continue
+      const { x, y } = nestLabelPosition(c, world.scale.x)
+      label.scale.set(inv)
+      label.position.set(x, y)
+    }
+  }
+
+  function setColonyStats(colonies: ColonyStats[]) {
+    lastColonyStats = colonies
+    for (const c of colonies) {
+      const label = colonyLabels.get(c.id)
+      if (label) label.text = `Colony ${c.id} · ${c.population.toLocaleString()}`
+    }
+  }
+
   function updatePheromoneTexture() {
     const buffers = buffersRef.current
     if (
@@ -224,6 +293,18 @@ export async function createSimRenderer(
     pheromoneTexture?.source.update()
   }
 
+  // scale is a static particle property, so it is only re-uploaded on zoom
+  function updateAntScale() {
+    const s = antScaleForZoom(world.scale.x)
+    if (Math.abs(s - antScale) < 0.01) return
+    antScale = s
+    for (const p of particles) {
+      p.scaleX = s
+      p.scaleY = s
+    }
+    antContainer.update()
+  }
+
   function fitCamera() {
     if (!worldMeta) return
     const scale = Math.min(
@@ -235,11 +316,14 @@ export async function createSimRenderer(
       (app.screen.width - worldMeta.worldWidth * scale) / 2,
       (app.screen.height - worldMeta.worldHeight * scale) / 2,
     )
+    updateAntScale()
+    updateLabelPlacement()
   }
 
   function resetWorld(init: WorldInit) {
     if (destroyed) return
     worldMeta = init
+    antTints = buildAntTints(init.colonies)
 
     // terrain
     const terrainCanvas = paintTerrain(init)
@@ -263,6 +347,7 @@ export async function createSimRenderer(
     pheromoneSprite.height = init.worldHeight
 
     redrawColonies()
+    rebuildColonyLabels()
     redrawFood()
     for (const p of particles) p.alpha = 0
     fitCamera()
@@ -294,21 +379,25 @@ export async function createSimRenderer(
     const sx = worldMeta.worldWidth / 65535
     const sy = worldMeta.worldHeight / 65535
     const prevCount = prev.count
+    const stepFraction = app.ticker.deltaMS / interval
 
     for (let i = 0; i < count; i++) {
       const p = particles[i]
       if (i < prevCount) {
-        p.x = (prev.x[i] + (curr.x[i] - prev.x[i]) * t) * sx
-        p.y = (prev.y[i] + (curr.y[i] - prev.y[i]) * t) * sy
+        const dx = (curr.x[i] - prev.x[i]) * sx
+        const dy = (curr.y[i] - prev.y[i]) * sy
+        p.x = prev.x[i] * sx + dx * t
+        p.y = prev.y[i] * sy + dy * t
+        gaitPhase[i] = advanceGait(gaitPhase[i], Math.hypot(dx, dy), stepFraction)
         // shortest-arc heading interpolation in quantized space
         const dh = ((curr.heading[i] - prev.heading[i] + 384) & 255) - 128
-        p.rotation = (prev.heading[i] + dh * t) * HEADING_TO_RAD
+        p.rotation = (prev.heading[i] + dh * t) * HEADING_TO_RAD + gaitSway(gaitPhase[i])
       } else {
         p.x = curr.x[i] * sx
         p.y = curr.y[i] * sy
         p.rotation = curr.heading[i] * HEADING_TO_RAD
       }
-      p.tint = TINTS[curr.flags[i] & 7]
+      p.tint = antTints[curr.flags[i]]
       p.alpha = 1
     }
     for (let i = count; i < particles.length; i++) {
@@ -356,6 +445,8 @@ export async function createSimRenderer(
     world.position.x = mx - ((mx - world.position.x) / oldScale) * newScale
     world.position.y = my - ((my - world.position.y) / oldScale) * newScale
     world.scale.set(newScale)
+    updateAntScale()
+    updateLabelPlacement()
   }
 
   canvas.addEventListener('pointerdown', onPointerDown)
@@ -366,6 +457,7 @@ export async function createSimRenderer(
 
   return {
     resetWorld,
+    setColonyStats,
     getFps: () => app.ticker.FPS,
     destroy() {
       destroyed = true
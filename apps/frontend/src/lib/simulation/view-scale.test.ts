@@ -0,0 +1,47 @@
+import { describe, expect, it } from 'vitest'
+import { antScaleForZoom, nestLabelPosition } from './view-scale'
+
+describe('antScaleForZoom', () => {
+  it('draws ants life-size when zoomed in', () => {
+    expect(antScaleForZoom(1)).toBe(1)
+    expect(antScaleForZoom(4)).toBe(1)
+  })
+
+  it('grows ants as the camera zooms out', () => {
+    expect(antScaleForZoom(0.25)).toBeGreaterThan(antScaleForZoom(0.5))
+    expect(antScaleForZoom(0.1)).toBeGreaterThan(antScaleForZoom(0.25))
+  })
+
+  it('keeps ants at least six pixels long on screen', () => {
+    expect(antScaleForZoom(0.2) * 9.3 * 0.2).toBeCloseTo(6)
+  })
+
+  it('stops growing at the cap', () => {
+    expect(antScaleForZoom(0.01)).toBe(8)
+    expect(antScaleForZoom(0.001)).toBe(8)
+  })
+})
+
+describe('nestLabelPosition', () => {
+  const nest = { x: 400, y: 300, radius: 20 }
+  // what the world container does: screen = offset + world * zoom
+  const toScreen = (y: number, zoom: number, offset: number) => offset + y * zoom
+
+  it('centres the label over the nest', () => {
+    expect(nestLabelPosition(nest, 1).x).toBe(400)
+    expect(nestLabelPosition(nest, 0.1).x).toBe(400)
+  })
+
+  it('keeps the label six screen pixels above the outer ring at any zoom', () => {
+    const ringTop = nest.y - nest.radius * 1.5
+    for (const [zoom, offset] of [
+      [1, 0],
+      [0.2, 35],
+      [3, -500],
+    ]) {
+      const gap =
+        toScreen(ringTop, zoom, offset) - toScreen(nestLabelPosition(nest, zoom).y, zoom, offset)
+      expect(gap).toBeCloseTo(6)
+    }
+  })
+})
@@ -0,0 +1,26 @@
+// Zoom-dependent sizes for the renderer.
+
+// the ant texture is about this many world units from abdomen to head; when
+// zoomed out far enough that it would shrink below MIN_ANT_SCREEN_LENGTH
+// pixels, ants are drawn larger than life so the colony stays readable
+const ANT_LENGTH = 9.3
+const MIN_ANT_SCREEN_LENGTH = 6
+const MAX_ANT_SCALE = 8
+
+/** Sprite scale for ants at a given world zoom (screen pixels per world unit). */
+export function antScaleForZoom(zoom: number): number {
+  return Math.min(Math.max(MIN_ANT_SCREEN_LENGTH / (ANT_LENGTH * zoom), 1), MAX_ANT_SCALE)
+}
+
+const LABEL_GAP = 6 // screen pixels between the nest's outer ring and its label
+
+/**
+ * World position of the bottom-centre anchor of a nest's label at a given
+ * zoom: above the outer ring, LABEL_GAP screen pixels clear of it.
+ */
+export function nestLabelPosition(
+  nest: { x: number; y: number; radius: number },
+  zoom: number,
+): { x: number; y: number } {
+  return { x: nest.x, y: nest.y - nest.radius * 1.5 - LABEL_GAP / zoom }
+}
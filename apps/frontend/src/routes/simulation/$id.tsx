@@ -72,6 +72,12 @@ function SimulationViewer() {
     }
   }, [init, rendererReady])
 
+  useEffect(() => {
+    if (stats && rendererReady) {
+      rendererRef.current?.setColonyStats(stats.colonies)
+    }
+  }, [stats, rendererReady])
+
   useEffect(() => {
     const timer = setInterval(() => {
       setFps(Math.round(rendererRef.current?.getFps() ?? 0))
@@ -132,6 +138,35 @@ function SimulationViewer() {
               Render <span className="font-mono text-white">{fps} fps</span>
             </span>
           </div>
+          {stats && stats.colonies.length > 0 && (
+            <table className="mt-2 w-full border-t border-zinc-700 pt-2 text-xs text-zinc-400">
+              <thead>
+                <tr className="text-left">
+                  <th className="font-normal">Colony</th>
+                  <th className="font-normal">Foragers</th>
+                  <th className="font-normal">Food/1k ticks</th>
+                  <th className="font-normal">Avg trip</th>
+                  <th className="font-normal">Starved/old/hazard</th>
+                </tr>
+              </thead>
+              <tbody className="font-mono text-white">
+                {stats.colonies.map((c) => (
+                  <tr key={c.id}>
+                    <td>#{c.id}</td>
+                    <td>{formatNumber(c.foragers)}</td>
+                    <td>{formatNumber(c.depositRate)}</td>
+                    <td>
+                      {c.avgTripTicks === null ? '-' : `${formatNumber(c.avgTripTicks)} ticks`}
+                    </td>
+                    <td>
+                      {formatNumber(c.deaths.starvation)}/{formatNumber(c.deaths.oldAge)}/
+                      {formatNumber(c.deaths.hazard)}
+                    </td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          )}
         </div>
       </div>
 
@@ -0,0 +1,149 @@
+//! Headless batch mode (`--batch <seeds>`): runs one simulation per terrain
+//! seed side by side, ticking them in lockstep, and reports their stats
+//! together so runs can be compared. No server or database is involved.
+
+use rayon::prelude::*;
+
+use crate::cli::{CliArgs, StopCondition};
+use crate::config::SimConfig;
+use crate::simulation::SimulationState;
+use crate::stats::{self, StatsRecorder, StatsSample};
+
+/// Independent simulations advanced together, one tick at a time.
+pub struct MultiSimRunner {
+    sims: Vec<SimulationState>,
+}
+
+impl MultiSimRunner {
+    pub fn new(configs: Vec<SimConfig>) -> Self {
+        Self {
+            sims: configs.into_iter().map(SimulationState::new).collect(),
+        }
+    }
+
+    /// Advance every simulation that hasn't met `stop` by one tick. Returns
+    /// how many were still running.
+    pub fn tick(&mut self, stop: &StopCondition) -> usize {
+        self.sims
+            .par_iter_mut()
+            .filter(|sim| stop.reached(sim).is_none())
+            .map(SimulationState::tick)
+            .count()
+    }
+
+    pub fn sims(&self) -> &[SimulationState] {
+        &self.sims
+    }
+}
+
+pub fn run(base: &SimConfig, cli: &CliArgs) -> anyhow::Result<()> {
+    let configs: Vec<SimConfig> = cli
+        .batch
+        .iter()
+        .enumerate()
+        .map(|(k, &seed)| SimConfig {
+            simulation_id: k as i32,
+            terrain_seed: seed,
+            ..base.clone()
+        })
+        .collect();
+    tracing::info!(
+        "Batch: {} simulations (seeds {:?}) for {} ticks",
+        configs.len(),
+        cli.batch,
+        cli.ticks
+    );
+
+    let mut runner = MultiSimRunner::new(configs);
+    let mut recorders: Vec<(String, StatsRecorder)> = cli
+        .batch
+        .iter()
+        .map(|seed| {
+            (
+                seed.to_string(),
+                StatsRecorder::new(cli.stats_every, usize::MAX),
+            )
+        })
+        .collect();
+
+    let mut stopped = vec![false; cli.batch.len()];
+    let mut ran = 0;
+    while ran < cli.ticks && runner.tick(&cli.stop) > 0 {
+        ran += 1;
+        for ((_, recorder), sim) in recorders.iter_mut().zip(runner.sims()) {
+            recorder.maybe_record(sim);
+        }
+        for ((sim, seed), done) in runner.sims().iter().zip(&cli.batch).zip(&mut stopped) {
+            if let (false, Some(reason)) = (*done, cli.stop.reached(sim)) {
+                tracing::info!(
+                    "seed={} stopped at tick {}: {}",
+                    seed,
+                    sim.tick_count,
+                    reason
+                );
+                *done = true;
+            }
+        }
+        if ran.is_multiple_of(cli.stats_every) {
+            log_stats(&runner, &cli.batch);
+        }
+    }
+    if !ran.is_multiple_of(cli.stats_every) {
+        log_stats(&runner, &cli.batch);
+    }
+
+    if let Some(path) = &cli.stats_out {
+        stats::save_csv_multi(&recorders, path)?;
+        tracing::info!("Wrote stats CSV to {}", path.display());
+    }
+    Ok(())
+}
+
+fn log_stats(runner: &MultiSimRunner, seeds: &[u64]) {
+    for (sim, seed) in runner.sims().iter().zip(seeds) {
+        let s = StatsSample::capture(sim);
+        tracing::info!(
+            "seed={:<6} tick={:<8} ants={} colonies={} collected={:.0} colony_food={:.0}",
+            seed,
+            s.tick,
+            s.total_ants,
+            s.active_colonies,
+            s.total_food_collected,
+            s.colony_food,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_tick_in_lockstep_but_diverge() {
+        let mut runner = MultiSimRunner::new(
+            [1, 2]
+                .map(|terrain_seed| SimConfig {
+                    terrain_seed,
+                    ..SimConfig::small()
+                })
+                .into(),
+        );
+        let stop = StopCondition::default();
+        for _ in 0..50 {
+            assert_eq!(runner.tick(&stop), 2);
+        }
+
+        let [a, b] = runner.sims() else {
+            panic!("expected two simulations");
+        };
+        assert_eq!(a.tick_count, 50);
+        assert_eq!(b.tick_count, 50);
+        let n = a.ants.count.min(b.ants.count);
+        assert!(n > 0);
+        assert!(
+            (0..n)
+                .any(|i| a.ants.pos_x[i] != b.ants.pos_x[i] || a.ants.pos_y[i] != b.ants.pos_y[i]),
+            "different seeds left every ant in the same place"
+        );
+    }
+}
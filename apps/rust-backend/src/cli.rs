@@ -0,0 +1,177 @@
+use std::path::PathBuf;
+
+use crate::logging::LogFormat;
+use crate::simulation::SimulationState;
+
+/// Command-line options. Everything is optional; without arguments the
+/// simulator serves the latest active simulation with persistence only.
+#[derive(Debug)]
+pub struct CliArgs {
+    /// TOML file with base simulation parameters (see `SimConfig`).
+    pub config: Option<PathBuf>,
+    /// Write the recorded stats time series as CSV to this path on shutdown.
+    pub stats_out: Option<PathBuf>,
+    /// Sample the stats recorder every this many ticks.
+    pub stats_every: u64,
+    /// Record every tick's ant positions to this trace file.
+    pub record: Option<PathBuf>,
+    /// Replay a trace file instead of running the simulation.
+    pub replay: Option<PathBuf>,
+    /// Start from this snapshot file instead of the store's latest checkpoint;
+    /// needs `memory_store`.
+    pub snapshot_in: Option<PathBuf>,
+    /// Write a snapshot of the running simulation to this path on shutdown.
+    pub snapshot_out: Option<PathBuf>,
+    /// Run one headless simulation per terrain seed in lockstep and exit.
+    pub batch: Vec<u64>,
+    /// Ticks to run in batch mode.
+    pub ticks: u64,
+    pub stop: StopCondition,
+    pub log_format: LogFormat,
+    /// Keep simulations and checkpoints in memory instead of Postgres.
+    pub memory_store: bool,
+    /// Override `colony_spawn_interval` from the config.
+    pub spawn_interval: Option<u64>,
+    /// Override `colony_spawn_batch` from the config.
+    pub spawn_batch: Option<usize>,
+}
+
+/// Conditions that end a run early. In server mode the simulator saves a
+/// final checkpoint and exits; in batch mode each simulation stops ticking
+/// on its own and the batch ends once all have stopped.
+#[derive(Debug, Default)]
+pub struct StopCondition {
+    pub at_tick: Option<u64>,
+    /// Stop once no ants are left.
+    pub on_extinction: bool,
+    pub at_food_collected: Option<f32>,
+}
+
+impl StopCondition {
+    /// Why `sim` should stop, if it should.
+    pub fn reached(&self, sim: &SimulationState) -> Option<&'static str> {
+        if self.at_tick.is_some_and(|t| sim.tick_count >= t) {
+            Some("tick limit reached")
+        } else if self.on_extinction && sim.ants.count == 0 {
+            Some("all ants died")
+        } else if self
+            .at_food_collected
+            .is_some_and(|f| sim.total_food_collected >= f)
+        {
+            Some("food target reached")
+        } else {
+            None
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.at_tick.is_some() || self.on_extinction || self.at_food_collected.is_some()
+    }
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            config: None,
+            stats_out: None,
+            stats_every: 60,
+            record: None,
+            replay: None,
+            snapshot_in: None,
+            snapshot_out: None,
+            batch: Vec::new(),
+            ticks: 18_000,
+            stop: StopCondition::default(),
+            log_format: LogFormat::Text,
+            memory_store: false,
+            spawn_interval: None,
+            spawn_batch: None,
+        }
+    }
+}
+
+impl CliArgs {
+    pub fn parse() -> anyhow::Result<Self> {
+        let mut cli = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => cli.config = Some(value(&mut args, &arg)?.into()),
+                "--stats-out" => cli.stats_out = Some(value(&mut args, &arg)?.into()),
+                "--stats-every" => {
+                    cli.stats_every = value(&mut args, &arg)?.parse::<u64>()?.max(1);
+                }
+                "--record" => cli.record = Some(value(&mut args, &arg)?.into()),
+                "--replay" => cli.replay = Some(value(&mut args, &arg)?.into()),
+                "--snapshot-in" => cli.snapshot_in = Some(value(&mut args, &arg)?.into()),
+                "--snapshot-out" => cli.snapshot_out = Some(value(&mut args, &arg)?.into()),
+                "--batch" => {
+                    cli.batch = value(&mut args, &arg)?
+                        .split(',')
+                        .map(|s| s.trim().parse::<u64>())
+                        .collect::<Result<_, _>>()?;
+                }
+                "--ticks" => cli.ticks = value(&mut args, &arg)?.parse()?,
+                "--stop-at-tick" => cli.stop.at_tick = Some(value(&mut args, &arg)?.parse()?),
+                "--log-format" => cli.log_format = value(&mut args, &arg)?.parse()?,
+                "--spawn-interval" => cli.spawn_interval = Some(value(&mut args, &arg)?.parse()?),
+                "--spawn-batch" => cli.spawn_batch = Some(value(&mut args, &arg)?.parse()?),
+                "--stop-on-extinction" => cli.stop.on_extinction = true,
+                "--memory-store" => cli.memory_store = true,
+                "--stop-at-food-collected" => {
+                    cli.stop.at_food_collected = Some(value(&mut args, &arg)?.parse()?);
+                }
+                other => anyhow::bail!("unknown argument: {}", other),
+            }
+        }
+        if cli.record.is_some() && cli.replay.is_some() {
+            anyhow::bail!("--record and --replay are mutually exclusive");
+        }
+        if !cli.batch.is_empty() && (cli.record.is_some() || cli.replay.is_some()) {
+            anyhow::bail!("--batch cannot be combined with --record or --replay");
+        }
+        if cli.memory_store && (cli.replay.is_some() || !cli.batch.is_empty()) {
+            anyhow::bail!("--memory-store only applies to a live simulation server");
+        }
+        if (cli.snapshot_in.is_some() || cli.snapshot_out.is_some())
+            && (cli.replay.is_some() || !cli.batch.is_empty())
+        {
+            anyhow::bail!("--snapshot-in/--snapshot-out only apply to a live simulation server");
+        }
+        // the snapshot keeps its simulation id, so its checkpoints would land
+        // on whatever database simulation happens to share it
+        if cli.snapshot_in.is_some() && !cli.memory_store {
+            anyhow::bail!("--snapshot-in requires --memory-store");
+        }
+        if cli.replay.is_some() && cli.stop.is_set() {
+            anyhow::bail!("stop conditions don't apply to --replay");
+        }
+        Ok(cli)
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> anyhow::Result<String> {
+    args.next()
+        .ok_or_else(|| anyhow::anyhow!("{} requires a value", flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+
+    #[test]
+    fn a_tick_limit_is_reached_at_exactly_that_tick() {
+        let stop = StopCondition {
+            at_tick: Some(5),
+            ..Default::default()
+        };
+        let mut sim = SimulationState::new(SimConfig::small());
+        for _ in 0..5 {
+            assert_eq!(stop.reached(&sim), None, "tick {}", sim.tick_count);
+            sim.tick();
+        }
+        assert_eq!(sim.tick_count, 5);
+        assert_eq!(stop.reached(&sim), Some("tick limit reached"));
+    }
+}
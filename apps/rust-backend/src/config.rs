@@ -1,53 +1,252 @@
-use serde::Deserialize;
+use std::path::Path;
 
-#[derive(Clone, Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::food::FoodKind;
+use crate::simulation::hazard::Hazard;
+
+/// Simulation parameters. Every field can be set from an optional TOML file
+/// (`--config <path>`); missing fields keep their defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SimConfig {
     pub simulation_id: i32,
     pub world_width: f32,
     pub world_height: f32,
     pub tick_rate: f32,
+    /// Speed multiplier a simulation starts at, in ticks per loop frame.
+    /// 0 starts it paused at normal speed.
+    pub initial_speed: f32,
 
     pub ant_max_speed: f32,
+    /// Most food an ant carries at once, whether picked up at a source or
+    /// looted in a raid; a full load slows it down the most. 0 means no cap
+    /// and no slowdown.
+    pub ant_carry_capacity: f32,
+    /// Speed fraction left to an ant carrying a full load.
+    pub ant_laden_speed: f32,
+    /// Load, as a fraction of `ant_carry_capacity`, a forager collects
+    /// before heading home; 0 returns with the first pickup. A full ant
+    /// always heads home, and topping up never fills past capacity.
+    pub ant_return_fill: f32,
+    /// Ticks after its first pickup that a forager keeps topping up a
+    /// partial load before bringing it home anyway.
+    pub ant_top_up_ticks: u64,
+    /// Food an ant hands over per tick while standing in the nest; 0 (the
+    /// default) drops the whole load at once.
+    pub ant_unload_rate: f32,
     pub ant_turn_rate: f32,
     pub ant_wander_strength: f32,
     pub ant_sensor_distance: f32,
     pub ant_sensor_angle: f32,
     pub ant_pickup_radius: f32,
     pub ant_detection_radius: f32,
+    /// Half-angle (radians) of the cone ahead in which ants see food at the
+    /// full detection radius.
+    pub ant_vision_half_angle: f32,
+    /// Detection radius multiplier for food outside the vision cone.
+    pub ant_rear_vision: f32,
+    /// Per-tick chance that an ant in sight range notices a food source of
+    /// difficulty 1.0; easier sources are noticed proportionally sooner.
+    pub food_discovery_chance: f32,
+    /// Discovery chance multiplier for scouts.
+    pub scout_discovery_boost: f32,
+    /// Detection radius multiplier for scouts.
+    pub scout_detection_boost: f32,
+    /// Pheromone sensor angle multiplier for scouts.
+    pub scout_sensor_spread: f32,
+    /// Pheromone sensor distance multiplier for scouts.
+    pub scout_sensor_reach: f32,
+    /// Per-ant spread of pheromone sensor distance: each ant is born with a
+    /// fixed sensitivity in `1 ± spread`. Must be in [0, 1).
+    pub ant_sensitivity_spread: f32,
+    /// Sensitivity an ant gains with every load it brings home.
+    pub ant_sensitivity_learning: f32,
+    /// Most sensitivity an ant can gain through experience.
+    pub ant_sensitivity_max_gain: f32,
+    /// Weight of a sensed food trail versus wander when following it.
+    pub trail_follow_weight: f32,
+    /// Weight of the home gradient versus path integration when returning.
+    pub home_gradient_weight: f32,
+    /// Weight of a food trail leading homeward against the way home found
+    /// otherwise, for ants carrying food (0 = returning ants ignore trails).
+    pub home_trail_weight: f32,
+    /// Waypoints an ant keeps of its outbound path (0 = off). A returning ant
+    /// whose straight way home is walled off retraces them instead.
+    pub ant_path_waypoints: usize,
+    /// Distance walked between recorded waypoints.
+    pub ant_waypoint_spacing: f32,
+    /// Douglas-Peucker tolerance used to thin a recorded path.
+    pub ant_path_tolerance: f32,
+    /// Per-tick chance of a foraging ant starting a levy flight.
+    pub levy_probability: f32,
+    /// How much a strong trail underfoot suppresses levy flights: the chance
+    /// is scaled by `1 - levy_trail_damping * confidence`, where confidence
+    /// is the strongest trail of the ant's colony around it (0 to 1).
+    pub levy_trail_damping: f32,
+    /// Ticks at the start of a levy flight that get the speed boost.
+    pub levy_boost_ticks: u32,
+    pub boundary_steer_weight: f32,
+    pub wall_steer_weight: f32,
 
     pub pheromone_cell_size: f32,
     pub pheromone_food_deposit: f32,
     pub pheromone_evaporation: f32,
     pub pheromone_diffusion_rate: f32,
     pub pheromone_diffusion_interval: u64,
+    /// Drift of the food trail layer, in world units per second (0 = calm).
+    pub wind_speed: f32,
+    /// Direction the wind blows toward, in radians.
+    pub wind_direction: f32,
+    /// How strongly foragers discount food trail laid in their direction of
+    /// travel (0 = trails are isotropic, 1 = wrong-way trail is ignored).
+    pub pheromone_direction_bias: f32,
+    /// How much foragers trust food trail another colony laid, from 0
+    /// (ignored: each colony keeps its own trail network) to 1 (followed
+    /// like their own).
+    pub rival_trail_weight: f32,
+    /// Energy an ant spends per tick of laying food trail.
+    pub pheromone_energy_cost: f32,
+    /// Below this energy an ant's trail weakens linearly, down to none at 0.
+    pub pheromone_min_energy: f32,
+    /// Reach of the queen pheromone around a nest (0 = no queen pheromone).
+    /// Wandering workers that have no food to go on drift back up its
+    /// gradient instead of scattering across an empty map.
+    pub queen_pheromone_range: f32,
+    /// Weight of the pull toward the queen scent against the wander heading;
+    /// any food target or trail overrides it.
+    pub queen_pull_weight: f32,
 
     pub initial_ant_count: usize,
+    /// Ants each colony starts with. 0 shares `initial_ant_count` out
+    /// between the colonies instead.
+    pub colony_starting_population: usize,
     pub max_ants: usize,
     pub colony_radius: f32,
+    /// Colonies placed at the start; with more than one they are arranged by
+    /// `colony_layout`, joined by corridors and given evenly spread hues.
+    pub colony_count: usize,
+    /// Minimum distance between any two nests, so their clearings (2.5 nest
+    /// radii) never merge. Starting nests closer than this are pushed apart.
+    pub colony_spacing: f32,
+    pub colony_layout: ColonyLayout,
+    /// Relative worker/scout/soldier weights for the starting colony;
+    /// founded colonies inherit their parent's mix.
+    pub role_mix: [f32; 3],
+    /// Aggression of the starting colonies, 0 to 1: raises the share of
+    /// soldiers they raise and how far those soldiers patrol.
+    pub colony_aggression: f32,
 
     pub food_source_count: usize,
+    /// Food sources per million square world units, so bigger worlds get
+    /// more; 0 places exactly `food_source_count`.
+    pub food_source_density: f32,
     pub food_per_source: f32,
+    /// Range a source's nutrition per unit of food is rolled from. Colonies
+    /// store deposited food weighted by it, so rich food feeds more ants and
+    /// eggs than the same weight of poor food. The default `[1, 1]` makes all
+    /// food equal.
+    pub food_nutrition: [f32; 2],
+    /// Kinds new sources are drawn from by rarity weight, each with a fixed
+    /// nutrition; empty rolls every source's nutrition from `food_nutrition`.
+    pub food_kinds: Vec<FoodKind>,
+    /// Food an ant takes from a source per pickup; a source that runs out is
+    /// removed from the world (or goes dormant, see `food_respawn_ticks`).
+    pub food_pickup_amount: f32,
+    /// Ticks a depleted source stays dormant before growing back; 0 removes
+    /// depleted sources for good.
+    pub food_respawn_ticks: u64,
+    /// Fraction of its previous maximum a source grows back to. Each regrowth
+    /// shrinks it further, and a source that would regrow below one unit of
+    /// food is gone for good.
+    pub food_respawn_fraction: f32,
     pub food_min_distance_from_colony: f32,
+    /// Radius of the clearing carved around each generated food source.
+    pub food_clearing_radius: f32,
 
     pub boundary_margin: f32,
 
     /// Colony food consumed per living ant per tick (trophallaxis upkeep).
     pub colony_upkeep_per_ant: f32,
+    /// Fraction of a colony's stored food that spoils each tick (0, the
+    /// default, for none; 0.00005 halves a hoard in about 14k ticks).
+    pub colony_food_spoilage: f32,
     /// Vitality regained per tick while the colony can feed the ant.
     pub ant_feed_recovery: f32,
+    /// Extra vitality a fed ant regains per tick while inside its own nest
+    /// (0, the default, for none).
+    pub ant_nest_rest_recovery: f32,
     /// Vitality lost per tick when the colony has no food to spare.
     pub ant_starve_damage: f32,
     /// Base worker lifespan in ticks; ants die of old age past this.
     pub ant_lifespan_ticks: u64,
     /// Per-ant random spread added to the base lifespan.
     pub ant_lifespan_variation: u64,
+    /// Food the queen spends per egg.
     pub colony_spawn_cost: f32,
+    /// How much dearer eggs get as the colony grows: each costs
+    /// `colony_spawn_cost * (1 + growth * size / colony_max_population)`,
+    /// counting adults and brood. 0 keeps the cost flat.
+    pub colony_spawn_cost_growth: f32,
+    /// Food each colony placed at the start has in store.
+    pub colony_initial_food: f32,
+    /// Ticks between egg-laying rounds (at least 1).
     pub colony_spawn_interval: u64,
+    /// Most eggs a queen lays per round (at least 1).
     pub colony_spawn_batch: usize,
+    /// Ticks an egg takes to become a larva.
+    pub brood_egg_ticks: u32,
+    /// Ticks of feeding a larva needs before it hatches into an adult.
+    pub brood_larva_ticks: u32,
+    /// Food a larva eats over its whole development.
+    pub brood_larva_food: f32,
+    /// Larvae one nurse in the nest can feed per tick.
+    pub brood_larvae_per_nurse: usize,
+    /// Ticks between rebalancing each colony's workers between foraging and
+    /// nursing (at least 1).
+    pub colony_labor_interval: u64,
+    /// Most of a colony's workers that may nurse at once; halved while its
+    /// store can't feed the colony for a foraging window, freeing hands to
+    /// forage. 0 (the default) assigns no nurses: any unladen worker in the
+    /// nest feeds larvae instead.
+    pub worker_nurse_share: f32,
+    /// A colony stops spawning at this many members (the global `max_ants`
+    /// cap still applies on top).
+    pub colony_max_population: usize,
+    /// Stored food a full colony must hold before it sends out founders.
+    pub colony_founding_surplus: f32,
+    /// How long a colony must stay full and well-stocked before founding.
+    pub colony_founding_ticks: u32,
+    /// Workers that emigrate with the new colony.
+    pub colony_founding_group: usize,
+    /// Minimum distance between a new colony and any existing one.
+    pub colony_founding_distance: f32,
+    /// Fraction of the parent's stored food the founders take along.
+    pub colony_founding_food_share: f32,
     pub levy_cooldown_ticks: u32,
     pub levy_speed_boost: f32,
     pub scout_wander_boost: f32,
+    /// Size of the cells a colony tracks exploration in; scouts with no
+    /// food in sight or trail to follow head for the nearest unvisited one.
+    /// 0 (the default) = scouts just wander.
+    pub scout_explore_cell: f32,
     pub soldier_patrol_radius: f32,
+    /// Colonies at least this aggressive send soldiers to steal from the
+    /// richest rival's store; above 1 no colony raids.
+    pub raid_min_aggression: f32,
+    /// Food a rival must have stored to be worth raiding.
+    pub raid_min_rival_food: f32,
+    /// Share of a raiding colony's idle soldiers sent out each labor round.
+    pub raid_party_share: f32,
+    /// Food sources a colony remembers from scout reports; workers leaving
+    /// the nest are sent straight to one of them.
+    pub colony_known_food_capacity: usize,
+
+    pub hazards: Vec<Hazard>,
+    /// Width of the danger band laid around each hazard, which ants sense
+    /// and steer away from.
+    pub hazard_warning_distance: f32,
+    pub hazard_avoid_weight: f32,
 
     pub terrain_cell_size: f32,
     pub terrain_seed: u64,
@@ -63,28 +262,76 @@ impl Default for SimConfig {
             world_width: 4000.0,
             world_height: 3000.0,
             tick_rate: 60.0,
+            initial_speed: 1.0,
 
             ant_max_speed: 80.0,
+            ant_carry_capacity: 1.0,
+            ant_laden_speed: 0.7,
+            ant_return_fill: 0.0,
+            ant_top_up_ticks: 600,
+            ant_unload_rate: 0.0,
             ant_turn_rate: 4.0,
             ant_wander_strength: 0.6,
             ant_sensor_distance: 24.0,
             ant_sensor_angle: 0.5,
             ant_pickup_radius: 10.0,
             ant_detection_radius: 50.0,
+            ant_vision_half_angle: 1.2,
+            ant_rear_vision: 0.35,
+            food_discovery_chance: 0.05,
+            scout_discovery_boost: 2.0,
+            scout_detection_boost: 1.8,
+            scout_sensor_spread: 1.4,
+            scout_sensor_reach: 1.5,
+            ant_sensitivity_spread: 0.15,
+            ant_sensitivity_learning: 0.01,
+            ant_sensitivity_max_gain: 0.3,
+            trail_follow_weight: 0.7,
+            home_gradient_weight: 0.6,
+            home_trail_weight: 0.3,
+            ant_path_waypoints: 0,
+            ant_waypoint_spacing: 40.0,
+            ant_path_tolerance: 8.0,
+            levy_probability: 0.003,
+            levy_trail_damping: 0.8,
+            levy_boost_ticks: 30,
+            boundary_steer_weight: 3.0,
+            wall_steer_weight: 4.0,
 
             pheromone_cell_size: 8.0,
             pheromone_food_deposit: 0.03,
             pheromone_evaporation: 0.996,
             pheromone_diffusion_rate: 0.08,
             pheromone_diffusion_interval: 3,
+            wind_speed: 0.0,
+            wind_direction: 0.0,
+            pheromone_direction_bias: 0.75,
+            rival_trail_weight: 0.0,
+            pheromone_energy_cost: 0.002,
+            pheromone_min_energy: 20.0,
+            queen_pheromone_range: 0.0,
+            queen_pull_weight: 0.3,
 
             initial_ant_count: 5_000,
+            colony_starting_population: 0,
             max_ants: 50_000,
             colony_radius: 50.0,
+            colony_count: 1,
+            colony_spacing: 250.0,
+            colony_layout: ColonyLayout::Ring,
+            role_mix: [0.7, 0.2, 0.1],
+            colony_aggression: 0.5,
 
             food_source_count: 48,
+            food_source_density: 0.0,
             food_per_source: 2_500.0,
+            food_nutrition: [1.0, 1.0],
+            food_kinds: Vec::new(),
+            food_pickup_amount: 1.0,
+            food_respawn_ticks: 0,
+            food_respawn_fraction: 0.6,
             food_min_distance_from_colony: 500.0,
+            food_clearing_radius: 20.0,
 
             boundary_margin: 40.0,
 
@@ -92,17 +339,42 @@ impl Default for SimConfig {
             // of old age) but becomes fatal if foraging income dries up,
             // letting a starved colony collapse.
             colony_upkeep_per_ant: 0.0003,
+            colony_food_spoilage: 0.0,
             ant_feed_recovery: 0.05,
+            ant_nest_rest_recovery: 0.0,
             ant_starve_damage: 0.02,
             ant_lifespan_ticks: 27_000,
             ant_lifespan_variation: 9_000,
             colony_spawn_cost: 2.0,
+            colony_spawn_cost_growth: 0.0,
+            colony_initial_food: 200.0,
             colony_spawn_interval: 30,
             colony_spawn_batch: 8,
+            brood_egg_ticks: 300,
+            brood_larva_ticks: 600,
+            brood_larva_food: 1.5,
+            brood_larvae_per_nurse: 10,
+            colony_labor_interval: 60,
+            worker_nurse_share: 0.0,
+            colony_max_population: 20_000,
+            colony_founding_surplus: 500.0,
+            colony_founding_ticks: 1_800,
+            colony_founding_group: 400,
+            colony_founding_distance: 700.0,
+            colony_founding_food_share: 0.4,
             levy_cooldown_ticks: 180,
             levy_speed_boost: 3.0,
             scout_wander_boost: 1.6,
+            scout_explore_cell: 0.0,
             soldier_patrol_radius: 120.0,
+            raid_min_aggression: 0.7,
+            raid_min_rival_food: 150.0,
+            raid_party_share: 0.25,
+            colony_known_food_capacity: 8,
+
+            hazards: Vec::new(),
+            hazard_warning_distance: 60.0,
+            hazard_avoid_weight: 1.5,
 
             terrain_cell_size: 8.0,
             terrain_seed: 42,
@@ -113,41 +385,199 @@ impl Default for SimConfig {
     }
 }
 
+#[cfg(test)]
+impl SimConfig {
+    /// A small open world with one colony and few ants, cheap to tick in tests.
+    pub fn small() -> Self {
+        Self {
+            world_width: 1200.0,
+            world_height: 900.0,
+            initial_ant_count: 60,
+            max_ants: 600,
+            food_source_count: 6,
+            food_min_distance_from_colony: 200.0,
+            terrain_density: 0.0,
+            ..Self::default()
+        }
+    }
+}
+
+/// How the starting colonies are arranged in the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColonyLayout {
+    /// Evenly spaced across the middle of the world.
+    Line,
+    /// On a circle around the world center.
+    Ring,
+    /// Rows and columns filling the world.
+    Grid,
+}
+
 /// Optional per-simulation overrides stored in the `simulations.config` jsonb column.
 #[derive(Debug, Default, Deserialize)]
 pub struct SimOverrides {
     pub seed: Option<u64>,
+    pub speed: Option<f32>,
     pub terrain_density: Option<f32>,
     pub initial_ants: Option<usize>,
+    pub starting_population: Option<usize>,
     pub max_ants: Option<usize>,
     pub food_sources: Option<usize>,
+    pub food_density: Option<f32>,
     pub food_per_source: Option<f32>,
+    pub role_mix: Option<[f32; 3]>,
+    pub aggression: Option<f32>,
+    pub spawn_interval: Option<u64>,
+    pub spawn_batch: Option<usize>,
+    pub spawn_cost: Option<f32>,
+    pub spawn_cost_growth: Option<f32>,
+    pub initial_food: Option<f32>,
+    pub colonies: Option<usize>,
+    pub colony_layout: Option<ColonyLayout>,
+    pub hazards: Option<Vec<Hazard>>,
+}
+
+impl SimOverrides {
+    /// Parse a row's `config` column. `null` means no overrides; anything but
+    /// an object, or a value of the wrong type, is an error naming the key.
+    pub fn from_json(value: &serde_json::Value) -> anyhow::Result<Self> {
+        let fields = match value {
+            serde_json::Value::Null => return Ok(Self::default()),
+            serde_json::Value::Object(fields) => fields,
+            other => anyhow::bail!("expected a JSON object, got {}", other),
+        };
+        serde_json::from_value(value.clone()).map_err(|e| {
+            // serde doesn't say which field failed; find it one key at a time
+            let bad_key = fields.iter().find(|&(k, v)| {
+                serde_json::from_value::<Self>(serde_json::json!({ k: v })).is_err()
+            });
+            match bad_key {
+                Some((k, v)) => anyhow::anyhow!("{} = {}: {}", k, v, e),
+                None => e.into(),
+            }
+        })
+    }
 }
 
 impl SimConfig {
-    /// Build a config from a `simulations` table row.
-    pub fn from_row(id: i32, world_width: i32, world_height: i32, config_json: &serde_json::Value) -> Self {
+    /// Load base parameters from a TOML file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Reject settings the simulation can't run with.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.colony_spawn_interval < 1 {
+            anyhow::bail!("colony_spawn_interval must be at least 1");
+        }
+        if self.colony_spawn_batch < 1 {
+            anyhow::bail!("colony_spawn_batch must be at least 1");
+        }
+        if self.colony_labor_interval < 1 {
+            anyhow::bail!("colony_labor_interval must be at least 1");
+        }
+        if let Some(kind) = self
+            .food_kinds
+            .iter()
+            .find(|k| !(k.weight >= 0.0 && k.weight.is_finite()))
+        {
+            anyhow::bail!(
+                "food kind {:?} needs a finite, non-negative weight",
+                kind.name
+            );
+        }
+        if !(self.colony_spacing >= 0.0 && self.colony_spacing.is_finite()) {
+            anyhow::bail!("colony_spacing must be finite and not negative");
+        }
+        if !(0.0..1.0).contains(&self.ant_sensitivity_spread) {
+            anyhow::bail!("ant_sensitivity_spread must be at least 0 and below 1");
+        }
+        if !(self.ant_sensitivity_learning >= 0.0 && self.ant_sensitivity_max_gain >= 0.0) {
+            anyhow::bail!(
+                "ant_sensitivity_learning and ant_sensitivity_max_gain must not be negative"
+            );
+        }
+        Ok(())
+    }
+
+    /// Change one field of a running simulation. Only fields read afresh every
+    /// tick can be tuned; values are clamped like the matching overrides, and
+    /// a change that fails `validate` leaves the config as it was. The change
+    /// lasts for this run only: it isn't written to the simulation's overrides.
+    pub fn set_tunable(&mut self, key: &str, value: f32) -> anyhow::Result<()> {
+        if !value.is_finite() {
+            anyhow::bail!("{} must be a finite number", key);
+        }
+        let mut next = self.clone();
+        match key {
+            "pheromone_evaporation" => next.pheromone_evaporation = value.clamp(0.0, 1.0),
+            "pheromone_food_deposit" => next.pheromone_food_deposit = value.max(0.0),
+            "pheromone_diffusion_rate" => next.pheromone_diffusion_rate = value.clamp(0.0, 1.0),
+            "wind_speed" => next.wind_speed = value.max(0.0),
+            "wind_direction" => next.wind_direction = value,
+            "levy_probability" => next.levy_probability = value.clamp(0.0, 1.0),
+            "levy_trail_damping" => next.levy_trail_damping = value.clamp(0.0, 1.0),
+            "food_pickup_amount" => next.food_pickup_amount = value.max(0.0),
+            "food_respawn_ticks" => next.food_respawn_ticks = value.max(0.0) as u64,
+            "food_respawn_fraction" => next.food_respawn_fraction = value.clamp(0.0, 1.0),
+            "colony_upkeep_per_ant" => next.colony_upkeep_per_ant = value.max(0.0),
+            "colony_food_spoilage" => next.colony_food_spoilage = value.clamp(0.0, 1.0),
+            "colony_spawn_cost" => next.colony_spawn_cost = value.max(0.0),
+            "colony_spawn_cost_growth" => next.colony_spawn_cost_growth = value.max(0.0),
+            "colony_spawn_interval" => next.colony_spawn_interval = value.max(1.0) as u64,
+            "colony_spawn_batch" => next.colony_spawn_batch = value.max(1.0) as usize,
+            _ => anyhow::bail!("{} is not a tunable config field", key),
+        }
+        next.validate()?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Food sources placed at the start: `food_source_count`, or one per
+    /// `1 / food_source_density` million square units when a density is set.
+    pub fn initial_food_sources(&self) -> usize {
+        if self.food_source_density <= 0.0 {
+            return self.food_source_count;
+        }
+        let area_mpx = self.world_width * self.world_height / 1_000_000.0;
+        ((area_mpx * self.food_source_density).round() as usize).clamp(1, 500)
+    }
+
+    /// Build a config from a `simulations` table row (its `config` column
+    /// parsed with `SimOverrides::from_json`) on top of `base`.
+    pub fn from_row(
+        base: &SimConfig,
+        id: i32,
+        world_width: i32,
+        world_height: i32,
+        overrides: SimOverrides,
+    ) -> Self {
         let mut cfg = Self {
             simulation_id: id,
             world_width: world_width as f32,
             world_height: world_height as f32,
-            ..Self::default()
+            ..base.clone()
         };
 
-        let overrides: SimOverrides =
-            serde_json::from_value(config_json.clone()).unwrap_or_default();
-
         if let Some(seed) = overrides.seed {
             cfg.terrain_seed = seed;
         } else {
             cfg.terrain_seed = id as u64;
         }
+        if let Some(speed) = overrides.speed {
+            cfg.initial_speed = speed;
+        }
         if let Some(d) = overrides.terrain_density {
             cfg.terrain_density = d.clamp(0.0, 0.6);
         }
         if let Some(n) = overrides.initial_ants {
             cfg.initial_ant_count = n.min(cfg.max_ants);
         }
+        if let Some(n) = overrides.starting_population {
+            cfg.colony_starting_population = n;
+        }
         if let Some(n) = overrides.max_ants {
             cfg.max_ants = n.clamp(100, 200_000);
             cfg.initial_ant_count = cfg.initial_ant_count.min(cfg.max_ants);
@@ -155,9 +585,42 @@ impl SimConfig {
         if let Some(n) = overrides.food_sources {
             cfg.food_source_count = n.clamp(1, 500);
         }
+        if let Some(d) = overrides.food_density {
+            cfg.food_source_density = d.max(0.0);
+        }
         if let Some(f) = overrides.food_per_source {
             cfg.food_per_source = f.max(10.0);
         }
+        if let Some(mix) = overrides.role_mix {
+            cfg.role_mix = mix.map(|w| w.max(0.0));
+        }
+        if let Some(n) = overrides.spawn_interval {
+            cfg.colony_spawn_interval = n.max(1);
+        }
+        if let Some(n) = overrides.spawn_batch {
+            cfg.colony_spawn_batch = n.max(1);
+        }
+        if let Some(c) = overrides.spawn_cost {
+            cfg.colony_spawn_cost = c.max(0.0);
+        }
+        if let Some(g) = overrides.spawn_cost_growth {
+            cfg.colony_spawn_cost_growth = g.max(0.0);
+        }
+        if let Some(f) = overrides.initial_food {
+            cfg.colony_initial_food = f.max(0.0);
+        }
+        if let Some(a) = overrides.aggression {
+            cfg.colony_aggression = a.clamp(0.0, 1.0);
+        }
+        if let Some(n) = overrides.colonies {
+            cfg.colony_count = n.clamp(1, 16);
+        }
+        if let Some(layout) = overrides.colony_layout {
+            cfg.colony_layout = layout;
+        }
+        if let Some(hazards) = overrides.hazards {
+            cfg.hazards = hazards;
+        }
 
         // scale food spacing down for small worlds
         let max_dist = (cfg.world_width.min(cfg.world_height)) * 0.35;
@@ -166,3 +629,111 @@ impl SimConfig {
         cfg
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn overrides_parse_from_an_object() {
+        let o = SimOverrides::from_json(&json!({ "seed": 7, "aggression": 0.5 })).unwrap();
+        assert_eq!(o.seed, Some(7));
+        assert_eq!(o.aggression, Some(0.5));
+        assert!(SimOverrides::from_json(&serde_json::Value::Null)
+            .unwrap()
+            .speed
+            .is_none());
+    }
+
+    #[test]
+    fn integer_values_are_accepted_for_float_overrides() {
+        let o = SimOverrides::from_json(&json!({ "spawn_cost": 3, "speed": 2 })).unwrap();
+        assert_eq!(o.spawn_cost, Some(3.0));
+        assert_eq!(o.speed, Some(2.0));
+    }
+
+    #[test]
+    fn malformed_overrides_name_the_bad_key() {
+        let err = SimOverrides::from_json(&json!({ "seed": 1, "spawn_cost": "lots" }))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.starts_with("spawn_cost = \"lots\""), "{err}");
+
+        let err = SimOverrides::from_json(&json!([1, 2]))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("expected a JSON object"), "{err}");
+    }
+
+    #[test]
+    fn sensitivity_settings_are_bounded() {
+        assert!(SimConfig::default().validate().is_ok());
+        for spread in [-0.1, 1.0, f32::NAN] {
+            let cfg = SimConfig {
+                ant_sensitivity_spread: spread,
+                ..SimConfig::default()
+            };
+            assert!(cfg.validate().is_err(), "spread {spread}");
+        }
+        let cfg = SimConfig {
+            ant_sensitivity_learning: -0.01,
+            ..SimConfig::default()
+        };
+        assert!(cfg.validate().is_err());
+        let cfg = SimConfig {
+            ant_sensitivity_max_gain: f32::NAN,
+            ..SimConfig::default()
+        };
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn colony_spacing_must_be_a_distance() {
+        for spacing in [-1.0, f32::NAN, f32::INFINITY] {
+            let cfg = SimConfig {
+                colony_spacing: spacing,
+                ..SimConfig::default()
+            };
+            assert!(cfg.validate().is_err(), "spacing {spacing}");
+        }
+    }
+
+    #[test]
+    fn food_source_count_scales_with_world_area() {
+        let sources = |world_width: f32, world_height: f32, food_source_density: f32| {
+            SimConfig {
+                world_width,
+                world_height,
+                food_source_density,
+                food_source_count: 48,
+                ..SimConfig::default()
+            }
+            .initial_food_sources()
+        };
+        assert_eq!(sources(1000.0, 1000.0, 10.0), 10);
+        assert_eq!(sources(2000.0, 2000.0, 10.0), 40);
+        // clamped to 1..=500
+        assert_eq!(sources(100.0, 100.0, 10.0), 1);
+        assert_eq!(sources(10_000.0, 10_000.0, 10.0), 500);
+        // no density: the fixed count, whatever the size
+        assert_eq!(sources(1000.0, 1000.0, 0.0), 48);
+        assert_eq!(sources(10_000.0, 10_000.0, 0.0), 48);
+    }
+
+    #[test]
+    fn tunables_change_only_known_fields() {
+        let mut cfg = SimConfig::default();
+        cfg.set_tunable("pheromone_evaporation", 0.9).unwrap();
+        assert_eq!(cfg.pheromone_evaporation, 0.9);
+        cfg.set_tunable("colony_spawn_interval", 0.0).unwrap();
+        assert_eq!(cfg.colony_spawn_interval, 1);
+
+        let before = format!("{cfg:?}");
+        assert!(cfg.set_tunable("world_width", 10.0).is_err());
+        assert!(cfg.set_tunable("wind_speed", f32::NAN).is_err());
+        assert_eq!(format!("{cfg:?}"), before);
+    }
+}
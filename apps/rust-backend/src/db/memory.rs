@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use tracing::info;
+
+use super::{
+    decode_checkpoint, CheckpointData, SimulationRow, SimulationStore, SimulationSummary,
+    CHECKPOINTS_KEPT,
+};
+use crate::config::SimConfig;
+
+/// Keeps simulations and checkpoints in process memory, for running the
+/// server without Postgres. Switching simulations resumes from their last
+/// checkpoint, but nothing survives a restart and the stats history is not
+/// kept.
+pub struct MemoryStore {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Oldest first.
+    simulations: Vec<MemorySimulation>,
+    /// Checkpoint blobs per simulation, oldest first.
+    checkpoints: HashMap<i32, Vec<(u64, Vec<u8>)>>,
+}
+
+struct MemorySimulation {
+    row: SimulationRow,
+    name: String,
+    is_active: bool,
+}
+
+impl MemoryStore {
+    /// A store holding one active simulation (id 1) with the world size of
+    /// `base` and no overrides.
+    pub fn new(base: &SimConfig) -> Self {
+        let default = MemorySimulation {
+            row: SimulationRow {
+                id: 1,
+                world_width: base.world_width as i32,
+                world_height: base.world_height as i32,
+                config: serde_json::json!({}),
+            },
+            name: "default".into(),
+            is_active: true,
+        };
+        Self {
+            inner: Mutex::new(Inner {
+                simulations: vec![default],
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Add an inactive simulation with the next free id and return that id.
+    #[cfg(test)]
+    pub fn insert_simulation(&self, name: &str, base: &SimConfig) -> i32 {
+        let mut inner = self.lock();
+        let id = inner
+            .simulations
+            .iter()
+            .map(|s| s.row.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        inner.simulations.push(MemorySimulation {
+            row: SimulationRow {
+                id,
+                world_width: base.world_width as i32,
+                world_height: base.world_height as i32,
+                config: serde_json::json!({}),
+            },
+            name: name.into(),
+            is_active: false,
+        });
+        id
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl SimulationStore for MemoryStore {
+    async fn load_simulation_row(
+        &self,
+        simulation_id: i32,
+    ) -> anyhow::Result<Option<SimulationRow>> {
+        Ok(self
+            .lock()
+            .simulations
+            .iter()
+            .find(|s| s.row.id == simulation_id)
+            .map(|s| s.row.clone()))
+    }
+
+    async fn load_latest_active_simulation(&self) -> anyhow::Result<Option<SimulationRow>> {
+        Ok(self
+            .lock()
+            .simulations
+            .iter()
+            .rev()
+            .find(|s| s.is_active)
+            .map(|s| s.row.clone()))
+    }
+
+    async fn list_simulations(&self) -> anyhow::Result<Vec<SimulationSummary>> {
+        let inner = self.lock();
+        Ok(inner
+            .simulations
+            .iter()
+            .rev()
+            .map(|s| SimulationSummary {
+                id: s.row.id,
+                name: s.name.clone(),
+                is_active: Some(s.is_active),
+                tick: inner
+                    .checkpoints
+                    .get(&s.row.id)
+                    .and_then(|cps| cps.iter().map(|&(tick, _)| tick as i64).max()),
+            })
+            .collect())
+    }
+
+    async fn activate_simulation(&self, simulation_id: i32) -> anyhow::Result<bool> {
+        let mut inner = self.lock();
+        if !inner.simulations.iter().any(|s| s.row.id == simulation_id) {
+            return Ok(false);
+        }
+        for s in &mut inner.simulations {
+            s.is_active = s.row.id == simulation_id;
+        }
+        Ok(true)
+    }
+
+    async fn save_checkpoint_blob(
+        &self,
+        simulation_id: i32,
+        tick: u64,
+        blob: &[u8],
+        _summary: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let mut inner = self.lock();
+        let cps = inner.checkpoints.entry(simulation_id).or_default();
+        cps.push((tick, blob.to_vec()));
+        cps.sort_by_key(|&(tick, _)| tick);
+        let excess = cps.len().saturating_sub(CHECKPOINTS_KEPT);
+        cps.drain(..excess);
+        info!(
+            "Saved checkpoint in memory: sim={} tick={} size={}KB",
+            simulation_id,
+            tick,
+            blob.len() / 1024
+        );
+        Ok(())
+    }
+
+    async fn save_stats(
+        &self,
+        _simulation_id: i32,
+        _tick: u64,
+        _total_ants: i32,
+        _food_collected: f32,
+        _colony_stats: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn load_latest_checkpoint(
+        &self,
+        simulation_id: i32,
+    ) -> anyhow::Result<Option<CheckpointData>> {
+        let inner = self.lock();
+        let Some((_, blob)) = inner.checkpoints.get(&simulation_id).and_then(|c| c.last()) else {
+            return Ok(None);
+        };
+        decode_checkpoint(simulation_id, blob).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::SimulationState;
+
+    #[tokio::test]
+    async fn checkpoints_round_trip_through_the_store() {
+        let store = MemoryStore::new(&SimConfig::small());
+        let row = store
+            .load_latest_active_simulation()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.id, 1);
+        assert!(store.load_latest_checkpoint(1).await.unwrap().is_none());
+
+        let mut sim = SimulationState::new(SimConfig::small());
+        for _ in 0..5 {
+            sim.tick();
+        }
+        let cp = sim.to_checkpoint();
+        let blob = cp.encode().unwrap();
+        store
+            .save_checkpoint_blob(1, cp.tick_count, &blob, &serde_json::json!({}))
+            .await
+            .unwrap();
+
+        let back = store.load_latest_checkpoint(1).await.unwrap().unwrap();
+        assert_eq!(back.tick_count, 5);
+        assert_eq!(back.ants.count, sim.ants.count);
+        assert_eq!(back.ants.pos_x, sim.ants.pos_x);
+        assert_eq!(back.ants.pos_y, sim.ants.pos_y);
+
+        let listed = store.list_simulations().await.unwrap();
+        assert_eq!(listed[0].tick, Some(5));
+    }
+
+    #[tokio::test]
+    async fn only_the_newest_checkpoints_are_kept() {
+        let store = MemoryStore::new(&SimConfig::small());
+        let mut sim = SimulationState::new(SimConfig::small());
+        for _ in 0..CHECKPOINTS_KEPT + 2 {
+            sim.tick();
+            let cp = sim.to_checkpoint();
+            store
+                .save_checkpoint_blob(
+                    1,
+                    cp.tick_count,
+                    &cp.encode().unwrap(),
+                    &serde_json::json!({}),
+                )
+                .await
+                .unwrap();
+        }
+        let kept: Vec<u64> = store.lock().checkpoints[&1]
+            .iter()
+            .map(|&(tick, _)| tick)
+            .collect();
+        assert_eq!(kept, [3, 4, 5]);
+        let newest = store.load_latest_checkpoint(1).await.unwrap().unwrap();
+        assert_eq!(newest.tick_count, (CHECKPOINTS_KEPT + 2) as u64);
+    }
+}
@@ -1,34 +1,93 @@
+mod memory;
+mod postgres;
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
 use tracing::info;
 
+use crate::config::SimConfig;
 use crate::simulation::ant::AntStorage;
 use crate::simulation::colony::Colony;
-use crate::simulation::food::FoodSource;
+use crate::simulation::food::{DormantFood, FoodSource};
 use crate::simulation::pheromone::PheromoneField;
 use crate::simulation::terrain::Terrain;
 use crate::simulation::SimulationState;
 
+pub use memory::MemoryStore;
+pub use postgres::PgStore;
+
+/// Checkpoints kept per simulation; older ones are pruned on save.
+const CHECKPOINTS_KEPT: usize = 3;
+
+/// Leading bytes of a snapshot file; bump the digit when its layout changes.
+/// The checkpoint inside carries its own version.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"ANTSNAP2";
+
+/// Leading bytes of a checkpoint blob, followed by a little-endian u16
+/// `CHECKPOINT_VERSION` and the bincode-encoded `CheckpointData`.
+const CHECKPOINT_MAGIC: &[u8; 4] = b"ANTC";
+/// Bump whenever `CheckpointData` or anything it holds changes layout; blobs
+/// of any other version are refused rather than misread.
+const CHECKPOINT_VERSION: u16 = 3;
+
 /// Serializable snapshot of everything needed to resume a simulation.
 #[derive(Serialize, Deserialize)]
 pub struct CheckpointData {
     pub tick_count: u64,
     pub total_food_collected: f32,
+    pub next_food_id: u32,
     pub ants: AntStorage,
     pub colonies: Vec<Colony>,
     pub food_sources: Vec<FoodSource>,
+    pub dormant_food: Vec<DormantFood>,
     pub pheromones: PheromoneField,
     pub terrain: Terrain,
 }
 
+impl CheckpointData {
+    /// Versioned blob for the `checkpoints` table and snapshot files.
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let mut blob = Vec::new();
+        blob.extend_from_slice(CHECKPOINT_MAGIC);
+        blob.extend_from_slice(&CHECKPOINT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut blob, self)?;
+        Ok(blob)
+    }
+
+    /// Read a blob written by `encode`. Blobs from before checkpoints were
+    /// versioned, or of another version, are an error.
+    pub fn decode(blob: &[u8]) -> anyhow::Result<Self> {
+        let Some(rest) = blob.strip_prefix(CHECKPOINT_MAGIC.as_slice()) else {
+            anyhow::bail!("checkpoint predates format versioning and cannot be read");
+        };
+        let Some((version, body)) = rest.split_first_chunk::<2>() else {
+            anyhow::bail!("checkpoint is truncated");
+        };
+        let version = u16::from_le_bytes(*version);
+        anyhow::ensure!(
+            version == CHECKPOINT_VERSION,
+            "checkpoint has format version {}, this build reads version {}",
+            version,
+            CHECKPOINT_VERSION
+        );
+        Ok(bincode::deserialize(body)?)
+    }
+}
+
 impl SimulationState {
     pub fn to_checkpoint(&self) -> CheckpointData {
         CheckpointData {
             tick_count: self.tick_count,
             total_food_collected: self.total_food_collected,
+            next_food_id: self.next_food_id,
             ants: self.ants.clone(),
             colonies: self.colonies.clone(),
             food_sources: self.food_sources.clone(),
+            dormant_food: self.dormant_food.clone(),
             pheromones: self.pheromones.clone(),
             terrain: self.terrain.clone(),
         }
@@ -37,9 +96,11 @@ impl SimulationState {
     pub fn restore_from_checkpoint(&mut self, cp: CheckpointData) {
         self.tick_count = cp.tick_count;
         self.total_food_collected = cp.total_food_collected;
+        self.next_food_id = cp.next_food_id;
         self.ants = cp.ants;
         self.colonies = cp.colonies;
         self.food_sources = cp.food_sources;
+        self.dormant_food = cp.dormant_food;
         self.pheromones = cp.pheromones;
         self.terrain = cp.terrain;
         info!(
@@ -47,10 +108,37 @@ impl SimulationState {
             self.tick_count
         );
     }
+
+    /// Write the config and a checkpoint to a single file that
+    /// `load_snapshot` can restore without any store.
+    pub fn save_snapshot(&self, path: &Path) -> anyhow::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(SNAPSHOT_MAGIC)?;
+        let checkpoint = self.to_checkpoint().encode()?;
+        bincode::serialize_into(&mut out, &(&self.config, checkpoint))?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Rebuild a simulation from a file written by `save_snapshot`.
+    pub fn load_snapshot(path: &Path) -> anyhow::Result<Self> {
+        let mut input = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+        input.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            anyhow::bail!("{} is not a simulation snapshot", path.display());
+        }
+        let (config, checkpoint): (SimConfig, Vec<u8>) = bincode::deserialize_from(input)?;
+        config.validate()?;
+        let cp = CheckpointData::decode(&checkpoint)?;
+        let mut sim = SimulationState::new(config);
+        sim.restore_from_checkpoint(cp);
+        Ok(sim)
+    }
 }
 
 /// A row from the `simulations` table.
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Clone, Debug, sqlx::FromRow)]
 pub struct SimulationRow {
     pub id: i32,
     pub world_width: i32,
@@ -58,111 +146,159 @@ pub struct SimulationRow {
     pub config: serde_json::Value,
 }
 
-pub async fn load_simulation_row(
-    pool: &PgPool,
-    simulation_id: i32,
-) -> anyhow::Result<Option<SimulationRow>> {
-    let row = sqlx::query_as::<_, SimulationRow>(
-        "SELECT id, world_width, world_height, config FROM simulations WHERE id = $1",
-    )
-    .bind(simulation_id)
-    .fetch_optional(pool)
-    .await?;
-    Ok(row)
+/// A simulation as listed by `GET /simulations`; `tick` is that of its
+/// latest checkpoint.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SimulationSummary {
+    pub id: i32,
+    pub name: String,
+    pub is_active: Option<bool>,
+    pub tick: Option<i64>,
 }
 
-pub async fn load_latest_active_simulation(
-    pool: &PgPool,
-) -> anyhow::Result<Option<SimulationRow>> {
-    let row = sqlx::query_as::<_, SimulationRow>(
-        "SELECT id, world_width, world_height, config FROM simulations WHERE is_active = true ORDER BY created_at DESC LIMIT 1",
-    )
-    .fetch_optional(pool)
-    .await?;
-    Ok(row)
-}
+/// Where simulations, their checkpoints and stats history live: Postgres
+/// (`PgStore`) normally, or process memory (`MemoryStore`) with
+/// `--memory-store`.
+#[async_trait]
+pub trait SimulationStore: Send + Sync {
+    async fn load_simulation_row(
+        &self,
+        simulation_id: i32,
+    ) -> anyhow::Result<Option<SimulationRow>>;
+
+    async fn load_latest_active_simulation(&self) -> anyhow::Result<Option<SimulationRow>>;
+
+    /// All simulations, newest first.
+    async fn list_simulations(&self) -> anyhow::Result<Vec<SimulationSummary>>;
+
+    /// Make `simulation_id` the only active simulation. Returns false if it
+    /// doesn't exist (nothing is changed then).
+    async fn activate_simulation(&self, simulation_id: i32) -> anyhow::Result<bool>;
+
+    /// Store a `CheckpointData::encode` blob, keeping only the newest
+    /// `CHECKPOINTS_KEPT` per simulation.
+    async fn save_checkpoint_blob(
+        &self,
+        simulation_id: i32,
+        tick: u64,
+        blob: &[u8],
+        summary: &serde_json::Value,
+    ) -> anyhow::Result<()>;
 
-pub async fn save_checkpoint_blob(
-    pool: &PgPool,
-    simulation_id: i32,
-    tick: u64,
-    blob: &[u8],
-    summary: &serde_json::Value,
-) -> anyhow::Result<()> {
-    sqlx::query(
-        "INSERT INTO simulation_checkpoints (simulation_id, tick, state_blob, summary) VALUES ($1, $2, $3, $4)",
-    )
-    .bind(simulation_id)
-    .bind(tick as i64)
-    .bind(blob)
-    .bind(summary)
-    .execute(pool)
-    .await?;
-
-    // keep only the 3 most recent checkpoints per simulation
-    sqlx::query(
-        "DELETE FROM simulation_checkpoints
-         WHERE simulation_id = $1
-           AND id NOT IN (
-               SELECT id FROM simulation_checkpoints
-               WHERE simulation_id = $1
-               ORDER BY tick DESC LIMIT 3
-           )",
-    )
-    .bind(simulation_id)
-    .execute(pool)
-    .await?;
+    async fn save_stats(
+        &self,
+        simulation_id: i32,
+        tick: u64,
+        total_ants: i32,
+        food_collected: f32,
+        colony_stats: &serde_json::Value,
+    ) -> anyhow::Result<()>;
 
+    async fn load_latest_checkpoint(
+        &self,
+        simulation_id: i32,
+    ) -> anyhow::Result<Option<CheckpointData>>;
+}
+
+fn decode_checkpoint(simulation_id: i32, blob: &[u8]) -> anyhow::Result<CheckpointData> {
+    let cp = CheckpointData::decode(blob)
+        .map_err(|e| anyhow::anyhow!("simulation {}: {}", simulation_id, e))?;
     info!(
-        "Saved checkpoint: sim={} tick={} size={}KB",
-        simulation_id,
-        tick,
-        blob.len() / 1024
+        "Loaded checkpoint for sim {} at tick {}",
+        simulation_id, cp.tick_count
     );
-    Ok(())
+    Ok(cp)
 }
 
-pub async fn save_stats(
-    pool: &PgPool,
-    simulation_id: i32,
-    tick: u64,
-    total_ants: i32,
-    food_collected: f32,
-    colony_stats: &serde_json::Value,
-) -> anyhow::Result<()> {
-    sqlx::query(
-        "INSERT INTO simulation_stats (simulation_id, tick, total_ants, food_collected, colony_stats) VALUES ($1, $2, $3, $4, $5)",
-    )
-    .bind(simulation_id)
-    .bind(tick as i64)
-    .bind(total_ants)
-    .bind(food_collected)
-    .bind(colony_stats)
-    .execute(pool)
-    .await?;
-    Ok(())
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::food::FoodKind;
+    use crate::simulation::hazard::{Hazard, HazardKind};
+
+    #[test]
+    fn checkpoint_blobs_are_versioned() {
+        let sim = SimulationState::new(SimConfig::small());
+        let cp = sim.to_checkpoint();
+        let blob = cp.encode().unwrap();
+        assert!(blob.starts_with(CHECKPOINT_MAGIC));
 
-pub async fn load_latest_checkpoint(
-    pool: &PgPool,
-    simulation_id: i32,
-) -> anyhow::Result<Option<CheckpointData>> {
-    let row: Option<(Vec<u8>,)> = sqlx::query_as(
-        "SELECT state_blob FROM simulation_checkpoints WHERE simulation_id = $1 ORDER BY tick DESC LIMIT 1",
-    )
-    .bind(simulation_id)
-    .fetch_optional(pool)
-    .await?;
-
-    match row {
-        Some((blob,)) => {
-            let cp: CheckpointData = bincode::deserialize(&blob)?;
-            info!(
-                "Loaded checkpoint for sim {} at tick {}",
-                simulation_id, cp.tick_count
-            );
-            Ok(Some(cp))
+        let back = CheckpointData::decode(&blob).unwrap();
+        assert_eq!(back.tick_count, cp.tick_count);
+        assert_eq!(back.ants.count, cp.ants.count);
+
+        // what was stored before blobs were versioned
+        let bare = bincode::serialize(&cp).unwrap();
+        let err = CheckpointData::decode(&bare).err().unwrap().to_string();
+        assert!(err.contains("predates"), "{err}");
+
+        let mut newer = blob.clone();
+        newer[CHECKPOINT_MAGIC.len()..][..2]
+            .copy_from_slice(&(CHECKPOINT_VERSION + 1).to_le_bytes());
+        let err = CheckpointData::decode(&newer).err().unwrap().to_string();
+        assert!(err.contains("format version"), "{err}");
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_a_file() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 2,
+            hazards: vec![Hazard {
+                kind: HazardKind::Flood,
+                x: 200.0,
+                y: 200.0,
+                radius: 60.0,
+                damage: 5.0,
+            }],
+            food_kinds: vec![
+                FoodKind {
+                    name: "seeds".into(),
+                    weight: 3.0,
+                    nutrition: 0.5,
+                },
+                FoodKind {
+                    name: "honeydew".into(),
+                    weight: 1.0,
+                    nutrition: 2.0,
+                },
+            ],
+            ..SimConfig::small()
+        });
+        for _ in 0..30 {
+            sim.tick();
+        }
+        let path = std::env::temp_dir().join(format!("ant-snapshot-{}.bin", std::process::id()));
+        sim.save_snapshot(&path).unwrap();
+        let loaded = SimulationState::load_snapshot(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.tick_count, sim.tick_count);
+        assert_eq!(loaded.ants.count, sim.ants.count);
+        assert_eq!(loaded.ants.id, sim.ants.id);
+        assert_eq!(loaded.ants.pos_x, sim.ants.pos_x);
+        assert_eq!(loaded.ants.pos_y, sim.ants.pos_y);
+        assert_eq!(loaded.config.terrain_seed, sim.config.terrain_seed);
+        assert_eq!(loaded.config.hazards.len(), 1);
+        assert_eq!(loaded.config.hazards[0].kind, HazardKind::Flood);
+        assert_eq!(loaded.config.food_kinds.len(), 2);
+
+        assert_eq!(loaded.colonies.len(), sim.colonies.len());
+        for (a, b) in loaded.colonies.iter().zip(&sim.colonies) {
+            assert_eq!((a.id, a.x, a.y), (b.id, b.x, b.y));
+            assert_eq!(a.population, b.population);
+            assert_eq!(a.food_stored, b.food_stored);
+        }
+        assert!(!sim.food_sources.is_empty());
+        assert!(sim.food_sources.iter().all(|f| f.kind.is_some()));
+        assert_eq!(loaded.food_sources.len(), sim.food_sources.len());
+        for (a, b) in loaded.food_sources.iter().zip(&sim.food_sources) {
+            assert_eq!((a.id, a.x, a.y, a.amount), (b.id, b.x, b.y, b.amount));
+            assert_eq!(a.kind, b.kind);
         }
-        None => Ok(None),
+        assert!(sim.pheromones.danger.iter().any(|&d| d > 0.0));
+        assert_eq!(loaded.pheromones.food, sim.pheromones.food);
+        assert_eq!(loaded.pheromones.home, sim.pheromones.home);
+        assert_eq!(loaded.pheromones.danger, sim.pheromones.danger);
     }
 }
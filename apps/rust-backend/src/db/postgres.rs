@@ -0,0 +1,157 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::info;
+
+use super::{
+    decode_checkpoint, CheckpointData, SimulationRow, SimulationStore, SimulationSummary,
+    CHECKPOINTS_KEPT,
+};
+
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SimulationStore for PgStore {
+    async fn load_simulation_row(
+        &self,
+        simulation_id: i32,
+    ) -> anyhow::Result<Option<SimulationRow>> {
+        let row = sqlx::query_as::<_, SimulationRow>(
+            "SELECT id, world_width, world_height, config FROM simulations WHERE id = $1",
+        )
+        .bind(simulation_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    async fn load_latest_active_simulation(&self) -> anyhow::Result<Option<SimulationRow>> {
+        let row = sqlx::query_as::<_, SimulationRow>(
+            "SELECT id, world_width, world_height, config FROM simulations WHERE is_active = true ORDER BY created_at DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    async fn list_simulations(&self) -> anyhow::Result<Vec<SimulationSummary>> {
+        let rows = sqlx::query_as::<_, SimulationSummary>(
+            "SELECT s.id, s.name, s.is_active,
+                    (SELECT max(c.tick) FROM simulation_checkpoints c WHERE c.simulation_id = s.id) AS tick
+             FROM simulations s
+             ORDER BY s.created_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn activate_simulation(&self, simulation_id: i32) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let found = sqlx::query(
+            "UPDATE simulations SET is_active = true, updated_at = now() WHERE id = $1",
+        )
+        .bind(simulation_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if !found {
+            return Ok(false);
+        }
+        sqlx::query(
+            "UPDATE simulations SET is_active = false, updated_at = now() WHERE id <> $1 AND is_active",
+        )
+        .bind(simulation_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn save_checkpoint_blob(
+        &self,
+        simulation_id: i32,
+        tick: u64,
+        blob: &[u8],
+        summary: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        // insert and prune together, so a failed prune doesn't leave a saved
+        // checkpoint behind that the writer then saves again on retry
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO simulation_checkpoints (simulation_id, tick, state_blob, summary) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(simulation_id)
+        .bind(tick as i64)
+        .bind(blob)
+        .bind(summary)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM simulation_checkpoints
+             WHERE simulation_id = $1
+               AND id NOT IN (
+                   SELECT id FROM simulation_checkpoints
+                   WHERE simulation_id = $1
+                   ORDER BY tick DESC LIMIT $2
+               )",
+        )
+        .bind(simulation_id)
+        .bind(CHECKPOINTS_KEPT as i64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        info!(
+            "Saved checkpoint: sim={} tick={} size={}KB",
+            simulation_id,
+            tick,
+            blob.len() / 1024
+        );
+        Ok(())
+    }
+
+    async fn save_stats(
+        &self,
+        simulation_id: i32,
+        tick: u64,
+        total_ants: i32,
+        food_collected: f32,
+        colony_stats: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO simulation_stats (simulation_id, tick, total_ants, food_collected, colony_stats) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(simulation_id)
+        .bind(tick as i64)
+        .bind(total_ants)
+        .bind(food_collected)
+        .bind(colony_stats)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_latest_checkpoint(
+        &self,
+        simulation_id: i32,
+    ) -> anyhow::Result<Option<CheckpointData>> {
+        let row: Option<(Vec<u8>,)> = sqlx::query_as(
+            "SELECT state_blob FROM simulation_checkpoints WHERE simulation_id = $1 ORDER BY tick DESC LIMIT 1",
+        )
+        .bind(simulation_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|(blob,)| decode_checkpoint(simulation_id, &blob))
+            .transpose()
+    }
+}
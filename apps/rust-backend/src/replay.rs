@@ -0,0 +1,141 @@
+//! Trace files for `--record` / `--replay`.
+//!
+//! A trace is a sequence of length-prefixed wire frames (u32 little-endian
+//! length, then the frame bytes exactly as sent over the WebSocket): an INIT
+//! frame whenever a simulation is (re)loaded, followed by one ANTS frame per
+//! tick.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Buffered trace writer; flushes periodically so a long recording never
+/// blocks the tick loop on disk I/O for more than one buffer's worth.
+pub struct TraceWriter {
+    out: BufWriter<File>,
+    last_flush: Instant,
+}
+
+impl TraceWriter {
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            out: BufWriter::with_capacity(1 << 20, File::create(path)?),
+            last_flush: Instant::now(),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.out.write_all(&(frame.len() as u32).to_le_bytes())?;
+        self.out.write_all(frame)?;
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.out.flush()?;
+            self.last_flush = Instant::now();
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+pub struct TraceReader {
+    input: BufReader<File>,
+}
+
+impl TraceReader {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            input: BufReader::new(File::open(path)?),
+        })
+    }
+
+    /// Next frame, or `None` at end of file.
+    pub fn next_frame(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        let mut len = [0u8; 4];
+        match self.input.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let mut frame = vec![0u8; u32::from_le_bytes(len) as usize];
+        self.input.read_exact(&mut frame)?;
+        Ok(Some(frame))
+    }
+
+    pub fn rewind(&mut self) -> std::io::Result<()> {
+        self.input.seek(SeekFrom::Start(0)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+    use crate::server::binary::{self, FRAME_ANTS, FRAME_INIT};
+    use crate::simulation::SimulationState;
+
+    /// Dequantized ant positions of an ANTS frame.
+    fn positions(frame: &[u8], world: (f32, f32)) -> Vec<(f32, f32)> {
+        frame[13..]
+            .chunks_exact(6)
+            .map(|a| {
+                let qx = u16::from_le_bytes([a[0], a[1]]) as f32;
+                let qy = u16::from_le_bytes([a[2], a[3]]) as f32;
+                (qx * world.0 / 65535.0, qy * world.1 / 65535.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replay_matches_the_recording_frame_for_frame() {
+        let path = std::env::temp_dir().join(format!("ant-trace-{}.bin", std::process::id()));
+        let mut sim = SimulationState::new(SimConfig::small());
+        let world = (sim.config.world_width, sim.config.world_height);
+
+        let mut recorded: Vec<Vec<(f32, f32)>> = Vec::new();
+        let mut writer = TraceWriter::create(&path).unwrap();
+        writer
+            .write_frame(&binary::encode_init(&sim, None))
+            .unwrap();
+        for _ in 0..20 {
+            sim.tick();
+            writer
+                .write_frame(&binary::encode_ants(&sim, None))
+                .unwrap();
+            let ants = &sim.ants;
+            recorded.push(
+                (0..ants.count)
+                    .map(|i| (ants.pos_x[i], ants.pos_y[i]))
+                    .collect(),
+            );
+        }
+        writer.flush().unwrap();
+
+        let mut reader = TraceReader::open(&path).unwrap();
+        let init = reader.next_frame().unwrap().unwrap();
+        assert_eq!(init[0], FRAME_INIT);
+        let mut replayed: Vec<Vec<(f32, f32)>> = Vec::new();
+        while let Some(frame) = reader.next_frame().unwrap() {
+            assert_eq!(frame[0], FRAME_ANTS);
+            replayed.push(positions(&frame, world));
+        }
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(replayed.len(), 20);
+        // one quantization step of slack
+        let (tx, ty) = (world.0 / 65535.0, world.1 / 65535.0);
+        for (tick, (got, want)) in replayed.iter().zip(&recorded).enumerate() {
+            assert_eq!(got.len(), want.len(), "ant count at tick {tick}");
+            for ((gx, gy), (wx, wy)) in got.iter().zip(want) {
+                assert!(
+                    (gx - wx).abs() <= tx && (gy - wy).abs() <= ty,
+                    "tick {tick}: replayed ({gx}, {gy}), recorded ({wx}, {wy})"
+                );
+            }
+        }
+    }
+}
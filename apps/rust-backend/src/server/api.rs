@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+
+use tokio::sync::oneshot;
+
+use super::messages::{AntsNearQuery, ControlMsg, SpawnAntsRequest, SpawnFoodRequest};
+use super::websocket::AppState;
+use crate::db::SimulationSummary;
+use crate::simulation::ant::role_from_name;
+use crate::stats::{AntSample, ColonySample};
+
+/// Upper bound on ants injected by a single request.
+const MAX_SPAWN_ANTS: usize = 1_000;
+/// Upper bound on ants returned by a single query.
+const MAX_QUERY_ANTS: usize = 1_000;
+/// Search radius when the query doesn't give one.
+const DEFAULT_QUERY_RADIUS: f32 = 100.0;
+
+/// All simulations in the DB, newest first.
+pub async fn list_simulations(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<SimulationSummary>>, StatusCode> {
+    let store = state
+        .store
+        .as_ref()
+        .ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+    match store.list_simulations().await {
+        Ok(rows) => Ok(Json(rows)),
+        Err(e) => {
+            tracing::warn!("Failed to list simulations: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Mark a simulation as the (only) active one and switch the running
+/// simulation over to it.
+pub async fn activate_simulation(
+    Path(simulation_id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+) -> StatusCode {
+    let Some(store) = state.store.as_ref() else {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    };
+    match store.activate_simulation(simulation_id).await {
+        Ok(true) => forward(&state, ControlMsg::Subscribe { simulation_id }),
+        Ok(false) => StatusCode::NOT_FOUND,
+        Err(e) => {
+            tracing::warn!("Failed to activate simulation {}: {}", simulation_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Queue a food source drop; applied by the simulation thread on its next tick.
+pub async fn spawn_food(
+    Path(simulation_id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SpawnFoodRequest>,
+) -> StatusCode {
+    forward(
+        &state,
+        ControlMsg::SpawnFood {
+            simulation_id,
+            x: req.x,
+            y: req.y,
+            amount: req.amount,
+        },
+    )
+}
+
+/// Queue an ant injection; applied by the simulation thread on its next tick.
+pub async fn spawn_ants(
+    Path(simulation_id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SpawnAntsRequest>,
+) -> StatusCode {
+    let role = match req.role.as_deref() {
+        Some(name) => match role_from_name(name) {
+            Some(role) => Some(role),
+            None => return StatusCode::UNPROCESSABLE_ENTITY,
+        },
+        None => None,
+    };
+    forward(
+        &state,
+        ControlMsg::SpawnAnts {
+            simulation_id,
+            x: req.x,
+            y: req.y,
+            count: req.count.unwrap_or(1).min(MAX_SPAWN_ANTS),
+            role,
+        },
+    )
+}
+
+/// Ants near a point, nearest first, read by the simulation thread between
+/// ticks. `404` if the simulation isn't the running one.
+pub async fn ants_near(
+    Path(simulation_id): Path<i32>,
+    Query(q): Query<AntsNearQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<AntSample>>, StatusCode> {
+    let radius = q.radius.unwrap_or(DEFAULT_QUERY_RADIUS);
+    if !(q.x.is_finite() && q.y.is_finite() && radius.is_finite()) || radius < 0.0 {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let (reply, rx) = oneshot::channel();
+    ask(
+        &state,
+        ControlMsg::QueryAnts {
+            simulation_id,
+            x: q.x,
+            y: q.y,
+            radius,
+            limit: MAX_QUERY_ANTS,
+            reply,
+        },
+        rx,
+    )
+    .await
+}
+
+/// Per-colony figures (population, stored food, brood, deaths) of the
+/// running simulation. `404` if the simulation isn't the running one.
+pub async fn colonies(
+    Path(simulation_id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ColonySample>>, StatusCode> {
+    let (reply, rx) = oneshot::channel();
+    ask(
+        &state,
+        ControlMsg::QueryColonies {
+            simulation_id,
+            reply,
+        },
+        rx,
+    )
+    .await
+}
+
+async fn ask<T>(
+    state: &AppState,
+    msg: ControlMsg,
+    rx: oneshot::Receiver<T>,
+) -> Result<Json<T>, StatusCode> {
+    state
+        .control_tx
+        .send(msg)
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    rx.await.map(Json).map_err(|_| StatusCode::NOT_FOUND)
+}
+
+fn forward(state: &AppState, msg: ControlMsg) -> StatusCode {
+    match state.control_tx.send(msg) {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
@@ -8,25 +8,37 @@
 //!                u32 pher_w, u32 pher_h, f32 pher_cell,
 //!                u16 colony_count x { u32 id, f32 x, f32 y, f32 radius, u16 hue },
 //!                u16 food_count x { u32 id, f32 x, f32 y, f32 amount, f32 max },
+//!                u16 hazard_count x { u8 kind, f32 x, f32 y, f32 radius },
+//!                  kind: 0 flood, 1 fire, 2 pesticide (since version 2)
 //!                terrain bits (ceil(w*h/8) bytes, LSB-first)
 //!
 //! ANTS (2):      u8 type, u64 tick, u32 count x { u16 qx, u16 qy, u8 heading, u8 flags }
 //!                qx/qy quantized to 0..65535 over world size,
 //!                heading quantized to 0..255 over 2*PI,
-//!                flags: bits 0-1 role, bit 2 carrying/returning
+//!                flags: bits 0-1 role, bit 2 carrying/returning,
+//!                       bits 3-7 colony slot (index into INIT's colony list, mod 32)
 //!
-//! PHEROMONE (3): u8 type, u64 tick, u32 w, u32 h, w*h u8 food, w*h u8 home
+//! PHEROMONE (3): u8 type, u64 tick, u32 x0, u32 y0, u32 w, u32 h,
+//!                w*h u8 food, w*h u8 home
+//!                the w x h block of cells starting at cell (x0, y0)
+//!                (x0 and y0 since version 3; before that the whole grid)
 //!
 //! FOOD (4):      u8 type, u64 tick, u16 count x { u32 id, f32 amount }
+//!
+//! A client that sent `SetViewport` gets its own INIT, ANTS and PHEROMONE
+//! frames, holding only the food, ants and pheromone cells in its viewport;
+//! the terrain, colonies and hazards in INIT are always complete.
 
 use crate::simulation::ant::AntState;
+use crate::simulation::geometry::Rect;
 use crate::simulation::SimulationState;
+use crate::stats::{ColonySample, StatsSample};
 
 pub const FRAME_INIT: u8 = 1;
 pub const FRAME_ANTS: u8 = 2;
 pub const FRAME_PHEROMONE: u8 = 3;
 pub const FRAME_FOOD: u8 = 4;
-pub const PROTOCOL_VERSION: u8 = 1;
+pub const PROTOCOL_VERSION: u8 = 3;
 
 struct Writer(Vec<u8>);
 
@@ -63,9 +75,16 @@ impl Writer {
     }
 }
 
-pub fn encode_init(sim: &SimulationState) -> Vec<u8> {
+/// World-space rectangle a client is looking at.
+pub type Viewport = Rect;
+
+pub fn encode_init(sim: &SimulationState, view: Option<&Viewport>) -> Vec<u8> {
     let terrain_bits = sim.terrain.packed_bits();
-    let mut w = Writer::with_capacity(64 + terrain_bits.len() + sim.food_sources.len() * 20);
+    let food: Vec<usize> = match view {
+        Some(view) => sim.food_in_rect(view),
+        None => (0..sim.food_sources.len()).collect(),
+    };
+    let mut w = Writer::with_capacity(64 + terrain_bits.len() + food.len() * 20);
 
     w.u8(FRAME_INIT);
     w.u8(PROTOCOL_VERSION);
@@ -91,8 +110,9 @@ pub fn encode_init(sim: &SimulationState) -> Vec<u8> {
         w.u16(c.color_hue);
     }
 
-    w.u16(sim.food_sources.len() as u16);
-    for f in &sim.food_sources {
+    w.u16(food.len() as u16);
+    for &j in &food {
+        let f = &sim.food_sources[j];
         w.u32(f.id);
         w.f32(f.x);
         w.f32(f.y);
@@ -100,23 +120,46 @@ pub fn encode_init(sim: &SimulationState) -> Vec<u8> {
         w.f32(f.max_amount);
     }
 
+    w.u16(sim.config.hazards.len() as u16);
+    for h in &sim.config.hazards {
+        w.u8(h.kind.code());
+        w.f32(h.x);
+        w.f32(h.y);
+        w.f32(h.radius);
+    }
+
     w.bytes(&terrain_bits);
     w.0
 }
 
-pub fn encode_ants(sim: &SimulationState) -> Vec<u8> {
-    let count = sim.ants.count;
-    let mut w = Writer::with_capacity(16 + count * 6);
+pub fn encode_ants(sim: &SimulationState, view: Option<&Viewport>) -> Vec<u8> {
+    let ants: Vec<usize> = match view {
+        Some(view) => sim.ants_in_rect(view),
+        None => (0..sim.ants.count).collect(),
+    };
+    let mut w = Writer::with_capacity(16 + ants.len() * 6);
 
     w.u8(FRAME_ANTS);
     w.u64(sim.tick_count);
-    w.u32(count as u32);
+    w.u32(ants.len() as u32);
 
     let sx = 65535.0 / sim.config.world_width;
     let sy = 65535.0 / sim.config.world_height;
     let sh = 256.0 / std::f32::consts::TAU;
 
-    for i in 0..count {
+    // colony id -> position in the INIT colony list
+    let max_id = sim
+        .colonies
+        .iter()
+        .map(|c| c.id as usize)
+        .max()
+        .unwrap_or(0);
+    let mut slot = vec![0u8; max_id + 1];
+    for (k, c) in sim.colonies.iter().enumerate() {
+        slot[c.id as usize] = (k % 32) as u8;
+    }
+
+    for i in ants {
         let qx = (sim.ants.pos_x[i] * sx).clamp(0.0, 65535.0) as u16;
         let qy = (sim.ants.pos_y[i] * sy).clamp(0.0, 65535.0) as u16;
         // heading is normalized to [-PI, PI]; shift into [0, TAU)
@@ -126,7 +169,11 @@ pub fn encode_ants(sim: &SimulationState) -> Vec<u8> {
         }
         let qh = (h * sh) as i32 & 0xFF;
         let carrying = (sim.ants.state[i] == AntState::Returning) as u8;
-        let flags = (sim.ants.ant_type[i] & 0b11) | (carrying << 2);
+        let colony = slot
+            .get(sim.ants.colony_id[i] as usize)
+            .copied()
+            .unwrap_or(0);
+        let flags = (sim.ants.ant_type[i] & 0b11) | (carrying << 2) | (colony << 3);
 
         w.u16(qx);
         w.u16(qy);
@@ -136,21 +183,28 @@ pub fn encode_ants(sim: &SimulationState) -> Vec<u8> {
     w.0
 }
 
-pub fn encode_pheromones(sim: &SimulationState) -> Vec<u8> {
-    let pw = sim.pheromones.grid_w;
-    let ph = sim.pheromones.grid_h;
-    let mut w = Writer::with_capacity(24 + pw * ph * 2);
+pub fn encode_pheromones(sim: &SimulationState, view: Option<&Viewport>) -> Vec<u8> {
+    let field = &sim.pheromones;
+    let (xs, ys) = match view {
+        Some(view) => field.cells_in_rect(view).unwrap_or((0..0, 0..0)),
+        None => (0..field.grid_w, 0..field.grid_h),
+    };
+    let mut w = Writer::with_capacity(32 + xs.len() * ys.len() * 2);
 
     w.u8(FRAME_PHEROMONE);
     w.u64(sim.tick_count);
-    w.u32(pw as u32);
-    w.u32(ph as u32);
+    w.u32(xs.start as u32);
+    w.u32(ys.start as u32);
+    w.u32(xs.len() as u32);
+    w.u32(ys.len() as u32);
 
-    for &v in &sim.pheromones.food {
-        w.u8((v.clamp(0.0, 1.0) * 255.0) as u8);
-    }
-    for &v in &sim.pheromones.home {
-        w.u8((v.clamp(0.0, 1.0) * 255.0) as u8);
+    for layer in [&field.food, &field.home] {
+        for y in ys.clone() {
+            let row = y * field.grid_w;
+            for &v in &layer[row + xs.start..row + xs.end] {
+                w.u8((v.clamp(0.0, 1.0) * 255.0) as u8);
+            }
+        }
     }
     w.0
 }
@@ -168,18 +222,202 @@ pub fn encode_food(sim: &SimulationState) -> Vec<u8> {
 }
 
 /// Human-readable stats for the HUD, sent as JSON text at ~1 Hz.
-pub fn encode_stats_json(sim: &SimulationState, tps: f32) -> String {
-    let colony_food: f32 = sim.colonies.iter().map(|c| c.food_stored).sum();
-    let world_food: f32 = sim.food_sources.iter().map(|f| f.amount).sum();
+pub fn encode_stats_json(
+    simulation_id: i32,
+    stats: &StatsSample,
+    colonies: &[ColonySample],
+    tps: f32,
+) -> String {
     serde_json::json!({
         "type": "stats",
-        "simulationId": sim.config.simulation_id,
-        "tick": sim.tick_count,
-        "antCount": sim.ants.count,
-        "foodCollected": sim.total_food_collected,
-        "colonyFood": colony_food,
-        "worldFood": world_food,
+        "simulationId": simulation_id,
+        "tick": stats.tick,
+        "antCount": stats.total_ants,
+        "activeColonies": stats.active_colonies,
+        "foodCollected": stats.total_food_collected,
+        "colonyFood": stats.colony_food,
+        "worldFood": stats.world_food,
         "tps": tps,
+        "colonies": colonies,
     })
     .to_string()
 }
+
+/// World size from an INIT frame header, `None` for any other frame.
+pub fn init_world_size(frame: &[u8]) -> Option<(f32, f32)> {
+    if frame.first() != Some(&FRAME_INIT) || frame.len() < 22 {
+        return None;
+    }
+    let w = f32::from_le_bytes(frame[14..18].try_into().ok()?);
+    let h = f32::from_le_bytes(frame[18..22].try_into().ok()?);
+    Some((w, h))
+}
+
+/// Copy an ANTS frame keeping only the ants inside `view`. Works on the
+/// quantized positions, for frames that can't be re-encoded from a
+/// simulation, like those of a replayed trace.
+pub fn cull_ants(frame: &[u8], view: &Viewport, world: (f32, f32)) -> Vec<u8> {
+    const HEADER: usize = 13;
+    const ANT: usize = 6;
+    if frame.len() < HEADER {
+        return frame.to_vec();
+    }
+
+    let quantize = |v: f32, size: f32| (v * 65535.0 / size).floor().clamp(0.0, 65535.0) as u16;
+    let xs = quantize(view.min_x, world.0)..=quantize(view.max_x, world.0);
+    let ys = quantize(view.min_y, world.1)..=quantize(view.max_y, world.1);
+
+    let mut out = Vec::with_capacity(frame.len());
+    out.extend_from_slice(&frame[..9]);
+    out.extend_from_slice(&[0; 4]);
+    let mut count = 0u32;
+    for ant in frame[HEADER..].chunks_exact(ANT) {
+        let qx = u16::from_le_bytes([ant[0], ant[1]]);
+        let qy = u16::from_le_bytes([ant[2], ant[3]]);
+        if xs.contains(&qx) && ys.contains(&qy) {
+            out.extend_from_slice(ant);
+            count += 1;
+        }
+    }
+    out[9..HEADER].copy_from_slice(&count.to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+    use crate::simulation::ant::{AntStorage, ROLE_WORKER};
+    use crate::simulation::food::FoodSource;
+
+    const VIEW: Viewport = Viewport {
+        min_x: 100.0,
+        min_y: 100.0,
+        max_x: 200.0,
+        max_y: 180.0,
+    };
+
+    fn food_at(id: u32, x: f32, y: f32) -> FoodSource {
+        FoodSource {
+            id,
+            x,
+            y,
+            amount: 50.0,
+            max_amount: 50.0,
+            discovery_difficulty: 0.5,
+            nutrition: 1.0,
+            kind: None,
+        }
+    }
+
+    /// Ids of the food sources listed in an INIT frame.
+    fn init_food_ids(frame: &[u8]) -> Vec<u32> {
+        let u16_at = |o: usize| u16::from_le_bytes([frame[o], frame[o + 1]]) as usize;
+        let colonies = u16_at(46);
+        let food = 48 + colonies * 18;
+        (0..u16_at(food))
+            .map(|k| {
+                let o = food + 2 + k * 20;
+                u32::from_le_bytes(frame[o..o + 4].try_into().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_viewport_init_lists_only_the_food_in_view() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        sim.food_sources = vec![
+            food_at(1, 150.0, 150.0), // inside
+            food_at(2, 200.0, 180.0), // on the max corner
+            food_at(3, 99.0, 150.0),  // just left of it
+            food_at(4, 600.0, 450.0), // far away
+        ];
+        let whole = encode_init(&sim, None);
+        let culled = encode_init(&sim, Some(&VIEW));
+        assert_eq!(init_food_ids(&whole), [1, 2, 3, 4]);
+        assert_eq!(init_food_ids(&culled), [1, 2]);
+        // the terrain still covers the whole world
+        assert_eq!(whole.len() - culled.len(), 2 * 20);
+    }
+
+    #[test]
+    fn a_viewport_ants_frame_holds_only_the_ants_in_view() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        sim.ants = AntStorage::new();
+        for (x, y) in [(150.0, 150.0), (100.0, 180.0), (201.0, 150.0)] {
+            sim.ants.add(x, y, 0, ROLE_WORKER, 1.0, 0.0);
+        }
+        let whole = encode_ants(&sim, None);
+        let culled = encode_ants(&sim, Some(&VIEW));
+        assert_eq!(culled[..9], whole[..9]);
+        assert_eq!(u32::from_le_bytes(culled[9..13].try_into().unwrap()), 2);
+        assert_eq!(culled[13..], whole[13..13 + 2 * 6]);
+    }
+
+    #[test]
+    fn a_viewport_pheromone_frame_holds_the_cells_in_view() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        let cell = sim.pheromones.cell_size;
+        let field = &mut sim.pheromones;
+        // trail on the min corner of the view, and a cell outside it
+        let (gx, gy) = ((VIEW.min_x / cell) as usize, (VIEW.min_y / cell) as usize);
+        field.food[gy * field.grid_w + gx] = 1.0;
+        field.food[gy * field.grid_w + gx - 1] = 1.0;
+
+        let frame = encode_pheromones(&sim, Some(&VIEW));
+        let u32_at = |o: usize| u32::from_le_bytes(frame[o..o + 4].try_into().unwrap()) as usize;
+        let (x0, y0, w, h) = (u32_at(9), u32_at(13), u32_at(17), u32_at(21));
+        let (xs, ys) = sim.pheromones.cells_in_rect(&VIEW).unwrap();
+        assert_eq!((x0, y0), (gx, gy));
+        assert_eq!((x0..x0 + w, y0..y0 + h), (xs, ys));
+        assert_eq!(frame.len(), 25 + w * h * 2);
+        let food = &frame[25..25 + w * h];
+        assert_eq!(food[0], 255);
+        assert_eq!(food.iter().filter(|&&v| v > 0).count(), 1);
+
+        // off the world: an empty block
+        let away = Viewport {
+            min_x: -100.0,
+            min_y: -100.0,
+            max_x: -10.0,
+            max_y: -10.0,
+        };
+        assert_eq!(encode_pheromones(&sim, Some(&away)).len(), 25);
+        let whole = encode_pheromones(&sim, None);
+        let grid = sim.pheromones.grid_w * sim.pheromones.grid_h;
+        assert_eq!(whole.len(), 25 + grid * 2);
+    }
+
+    #[test]
+    fn culling_keeps_ants_inside_the_viewport_and_on_its_edges() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        let world = (sim.config.world_width, sim.config.world_height);
+        sim.ants = AntStorage::new();
+        for (x, y) in [
+            (150.0, 150.0), // inside
+            (100.0, 100.0), // on the min corner
+            (200.0, 180.0), // on the max x edge
+            (201.0, 150.0), // just right of it
+            (150.0, 99.0),  // just above
+            (600.0, 450.0), // far away
+        ] {
+            sim.ants.add(x, y, 0, ROLE_WORKER, 1.0, 0.0);
+        }
+        let view = Viewport {
+            min_x: 100.0,
+            min_y: 100.0,
+            max_x: 200.0,
+            max_y: 180.0,
+        };
+
+        let frame = encode_ants(&sim, None);
+        let culled = cull_ants(&frame, &view, world);
+
+        assert_eq!(culled[..9], frame[..9]);
+        let count = u32::from_le_bytes(culled[9..13].try_into().unwrap());
+        assert_eq!(count, 3);
+        let kept: Vec<&[u8]> = culled[13..].chunks_exact(6).collect();
+        let sent: Vec<&[u8]> = frame[13..].chunks_exact(6).collect();
+        assert_eq!(kept, sent[..3]);
+    }
+}
@@ -1,14 +1,139 @@
+use std::sync::Arc;
+
 use serde::Deserialize;
+use tokio::sync::{mpsc, oneshot};
+
+use super::binary::Viewport;
+use crate::stats::{AntSample, ColonySample};
 
 /// JSON messages FROM the client.
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientMessage {
-    Subscribe { simulation_id: i32 },
+    Subscribe {
+        simulation_id: i32,
+    },
+    /// Only send food, ants and pheromones inside this world-space rectangle.
+    SetViewport {
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+    },
+    /// Go back to receiving the whole world.
+    ClearViewport,
+    /// The commands below apply to the subscribed simulation.
+    Pause,
+    Resume,
+    /// Simulation ticks per real tick period (1.0 = real time).
+    SetSpeed {
+        speed: f32,
+    },
+    SpawnFood {
+        x: f32,
+        y: f32,
+        amount: Option<f32>,
+    },
+    /// Change a tunable `SimConfig` field for the rest of this run (see
+    /// `SimConfig::set_tunable`); a rejected change is answered with an
+    /// `error` message.
+    SetConfig {
+        key: String,
+        value: f32,
+    },
+}
+
+/// Body of `POST /simulations/:id/food`.
+#[derive(Debug, Deserialize)]
+pub struct SpawnFoodRequest {
+    pub x: f32,
+    pub y: f32,
+    pub amount: Option<f32>,
+}
+
+/// Body of `POST /simulations/:id/ants`.
+#[derive(Debug, Deserialize)]
+pub struct SpawnAntsRequest {
+    pub x: f32,
+    pub y: f32,
+    pub count: Option<usize>,
+    /// "worker", "scout" or "soldier"; omitted = the usual role mix.
+    pub role: Option<String>,
+}
+
+/// Query string of `GET /simulations/:id/ants`.
+#[derive(Debug, Deserialize)]
+pub struct AntsNearQuery {
+    pub x: f32,
+    pub y: f32,
+    pub radius: Option<f32>,
 }
 
-/// Control messages forwarded from WebSocket handlers to the simulation thread.
+/// Control messages forwarded from WebSocket/HTTP handlers to the simulation thread.
 #[derive(Debug)]
 pub enum ControlMsg {
-    Subscribe { simulation_id: i32 },
+    Subscribe {
+        simulation_id: i32,
+    },
+    Pause {
+        simulation_id: i32,
+    },
+    Resume {
+        simulation_id: i32,
+    },
+    SetSpeed {
+        simulation_id: i32,
+        speed: f32,
+    },
+    SpawnFood {
+        simulation_id: i32,
+        x: f32,
+        y: f32,
+        amount: Option<f32>,
+    },
+    SpawnAnts {
+        simulation_id: i32,
+        x: f32,
+        y: f32,
+        count: usize,
+        role: Option<u8>,
+    },
+    /// Answered between ticks; the reply is dropped if the simulation isn't running.
+    QueryAnts {
+        simulation_id: i32,
+        x: f32,
+        y: f32,
+        radius: f32,
+        limit: usize,
+        reply: oneshot::Sender<Vec<AntSample>>,
+    },
+    QueryColonies {
+        simulation_id: i32,
+        reply: oneshot::Sender<Vec<ColonySample>>,
+    },
+    SetConfig {
+        simulation_id: i32,
+        key: String,
+        value: f32,
+        reply: oneshot::Sender<anyhow::Result<()>>,
+    },
+    /// A fresh INIT frame for one client that fell behind the broadcast,
+    /// culled to its viewport if it has one.
+    FullState {
+        simulation_id: i32,
+        viewport: Option<Viewport>,
+        reply: oneshot::Sender<Arc<Vec<u8>>>,
+    },
+    /// Start (or move) a client's viewport: from now on its INIT, ANTS and
+    /// PHEROMONE frames are encoded for it alone and sent on `frames`,
+    /// starting with an INIT for the new view.
+    SetViewport {
+        client: u64,
+        viewport: Viewport,
+        frames: mpsc::Sender<Arc<Vec<u8>>>,
+    },
+    /// The client went back to the shared frames, or disconnected.
+    ClearViewport {
+        client: u64,
+    },
 }
@@ -1,3 +1,4 @@
+pub mod api;
 pub mod binary;
 pub mod messages;
 pub mod websocket;
@@ -5,12 +6,14 @@ pub mod websocket;
 use std::sync::Arc;
 
 use axum::http::Method;
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
 use tokio::sync::broadcast;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 
+use crate::db::SimulationStore;
+
 use websocket::{ws_handler, AppState, BroadcastTx, ControlTx};
 
 pub fn create_broadcast() -> BroadcastTx {
@@ -22,25 +25,143 @@ pub async fn start_server(
     addr: &str,
     broadcast_tx: BroadcastTx,
     control_tx: ControlTx,
+    store: Option<Arc<dyn SimulationStore>>,
 ) -> anyhow::Result<()> {
-    let state = Arc::new(AppState {
+    let app = router(Arc::new(AppState {
         broadcast_tx,
         control_tx,
-    });
+        store,
+    }));
 
-    let app = Router::new()
+    info!("WebSocket server listening on {}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
         .route("/ws", get(ws_handler))
         .route("/health", get(|| async { "OK" }))
+        .route("/simulations", get(api::list_simulations))
+        .route("/simulations/:id/activate", post(api::activate_simulation))
+        .route("/simulations/:id/food", post(api::spawn_food))
+        .route(
+            "/simulations/:id/ants",
+            get(api::ants_near).post(api::spawn_ants),
+        )
+        .route("/simulations/:id/colonies", get(api::colonies))
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
-                .allow_methods([Method::GET])
+                .allow_methods([Method::GET, Method::POST])
                 .allow_headers(Any),
         )
-        .with_state(state);
+        .with_state(state)
+}
 
-    info!("WebSocket server listening on {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::config::SimConfig;
+    use crate::db::MemoryStore;
+    use crate::server::messages::ControlMsg;
+
+    #[tokio::test]
+    async fn posting_food_forwards_a_spawn_to_the_simulation() {
+        let (control_tx, control_rx) = mpsc::channel();
+        let app = router(Arc::new(AppState {
+            broadcast_tx: create_broadcast(),
+            control_tx,
+            store: None,
+        }));
+
+        let res = app
+            .oneshot(
+                Request::post("/simulations/7/food")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"x":120.5,"y":80,"amount":300}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        match control_rx.try_recv().unwrap() {
+            ControlMsg::SpawnFood {
+                simulation_id,
+                x,
+                y,
+                amount,
+            } => {
+                assert_eq!(simulation_id, 7);
+                assert_eq!((x, y), (120.5, 80.0));
+                assert_eq!(amount, Some(300.0));
+            }
+            _ => panic!("expected a SpawnFood message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn listing_and_activating_simulations_goes_through_the_store() {
+        let store = MemoryStore::new(&SimConfig::small());
+        let second = store.insert_simulation("second", &SimConfig::small());
+        let (control_tx, control_rx) = mpsc::channel();
+        let app = router(Arc::new(AppState {
+            broadcast_tx: create_broadcast(),
+            control_tx,
+            store: Some(Arc::new(store)),
+        }));
+        let list = |app: Router| async move {
+            let res = app
+                .oneshot(Request::get("/simulations").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let rows: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            rows.as_array()
+                .unwrap()
+                .iter()
+                .map(|r| (r["id"].as_i64().unwrap(), r["is_active"].as_bool().unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        // newest first
+        assert_eq!(list(app.clone()).await, [(second as i64, false), (1, true)]);
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::post(format!("/simulations/{second}/activate"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert!(matches!(
+            control_rx.try_recv().unwrap(),
+            ControlMsg::Subscribe { simulation_id } if simulation_id == second
+        ));
+        assert_eq!(list(app.clone()).await, [(second as i64, true), (1, false)]);
+
+        let res = app
+            .oneshot(
+                Request::post("/simulations/99/activate")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
 }
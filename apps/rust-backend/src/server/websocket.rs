@@ -1,13 +1,16 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{State, WebSocketUpgrade};
 use axum::response::IntoResponse;
 use futures_util::{SinkExt, StreamExt};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use tracing::{info, warn};
 
+use super::binary::{self, Viewport};
 use super::messages::{ClientMessage, ControlMsg};
+use crate::db::SimulationStore;
 
 /// Outbound frame, shared cheaply between all connected clients.
 #[derive(Clone)]
@@ -19,9 +22,18 @@ pub enum WsOut {
 pub type BroadcastTx = broadcast::Sender<WsOut>;
 pub type ControlTx = std::sync::mpsc::Sender<ControlMsg>;
 
+/// Viewport frames queued for one client before the simulation drops frames
+/// and resyncs it with a fresh INIT instead.
+pub const VIEWPORT_BUFFER: usize = 8;
+
+/// Tells connected clients apart when they register a viewport.
+static NEXT_CLIENT: AtomicU64 = AtomicU64::new(0);
+
 pub struct AppState {
     pub broadcast_tx: BroadcastTx,
     pub control_tx: ControlTx,
+    /// `None` when running without persistence.
+    pub store: Option<Arc<dyn SimulationStore>>,
 }
 
 pub async fn ws_handler(
@@ -35,50 +47,111 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     let (mut sender, mut receiver) = socket.split();
     let mut rx = state.broadcast_tx.subscribe();
     let control_tx = state.control_tx.clone();
+    let resync_tx = state.control_tx.clone();
+    let close_tx = state.control_tx.clone();
+    let client = NEXT_CLIENT.fetch_add(1, Ordering::Relaxed);
+    let (viewport_tx, viewport_rx) = watch::channel::<Option<Viewport>>(None);
+    let (subscribed_tx, subscribed_rx) = watch::channel::<Option<i32>>(None);
+    // replies meant for this client only
+    let (direct_tx, mut direct_rx) = mpsc::unbounded_channel::<String>();
+    // frames the simulation encoded for this client's viewport
+    let (frames_tx, mut frames_rx) = mpsc::channel::<Arc<Vec<u8>>>(VIEWPORT_BUFFER);
 
     info!("WebSocket client connected");
 
     let send_task = tokio::spawn(async move {
         loop {
-            match rx.recv().await {
-                Ok(WsOut::Binary(bytes)) => {
-                    if sender
-                        .send(Message::Binary(bytes.as_ref().clone()))
-                        .await
-                        .is_err()
-                    {
+            let msg = tokio::select! {
+                msg = rx.recv() => msg,
+                Some(text) = direct_rx.recv() => {
+                    if sender.send(Message::Text(text)).await.is_err() {
                         break;
                     }
+                    continue;
                 }
-                Ok(WsOut::Text(text)) => {
-                    if sender
-                        .send(Message::Text(text.as_ref().clone()))
-                        .await
-                        .is_err()
-                    {
+                Some(frame) = frames_rx.recv() => {
+                    if sender.send(Message::Binary(frame.as_ref().clone())).await.is_err() {
                         break;
                     }
+                    continue;
                 }
-                // client too slow for the broadcast buffer: skip missed frames
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    warn!("WebSocket client lagged, skipped {} frames", n);
+            };
+            let step = next_frame(msg, &viewport_rx, &subscribed_rx, &resync_tx).await;
+            match step {
+                Outbound::Send(frame) => {
+                    if sender.send(frame).await.is_err() {
+                        break;
+                    }
                 }
-                Err(broadcast::error::RecvError::Closed) => break,
+                Outbound::Skip => {}
+                Outbound::Close => break,
             }
         }
     });
 
     let recv_task = tokio::spawn(async move {
+        let mut subscribed: Option<i32> = None;
         while let Some(Ok(msg)) = receiver.next().await {
             if let Message::Text(text) = msg {
-                match serde_json::from_str::<ClientMessage>(&text) {
-                    Ok(ClientMessage::Subscribe { simulation_id }) => {
+                let msg = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        warn!("Failed to parse client message: {}", e);
+                        continue;
+                    }
+                };
+                match msg {
+                    ClientMessage::Subscribe { simulation_id } => {
                         info!("Client subscribed to simulation {}", simulation_id);
+                        subscribed = Some(simulation_id);
+                        let _ = subscribed_tx.send(subscribed);
                         let _ = control_tx.send(ControlMsg::Subscribe { simulation_id });
                     }
-                    Err(e) => {
-                        warn!("Failed to parse client message: {}", e);
+                    ClientMessage::SetViewport {
+                        min_x,
+                        min_y,
+                        max_x,
+                        max_y,
+                    } => {
+                        let viewport = Viewport {
+                            min_x: min_x.min(max_x),
+                            min_y: min_y.min(max_y),
+                            max_x: max_x.max(min_x),
+                            max_y: max_y.max(min_y),
+                        };
+                        let _ = viewport_tx.send(Some(viewport));
+                        let _ = control_tx.send(ControlMsg::SetViewport {
+                            client,
+                            viewport,
+                            frames: frames_tx.clone(),
+                        });
+                    }
+                    ClientMessage::ClearViewport => {
+                        let _ = viewport_tx.send(None);
+                        let _ = control_tx.send(ControlMsg::ClearViewport { client });
                     }
+                    ClientMessage::SetConfig { key, value } => {
+                        let Some(simulation_id) = subscribed else {
+                            warn!("Ignoring SetConfig from a client that has not subscribed");
+                            continue;
+                        };
+                        if let Some(error) =
+                            set_config(&control_tx, simulation_id, key, value).await
+                        {
+                            let _ = direct_tx.send(error);
+                        }
+                    }
+                    command => match subscribed {
+                        Some(simulation_id) => {
+                            if let Some(command) = sim_command(command, simulation_id) {
+                                let _ = control_tx.send(command);
+                            }
+                        }
+                        None => warn!(
+                            "Ignoring {:?} from a client that has not subscribed",
+                            command
+                        ),
+                    },
                 }
             }
         }
@@ -88,6 +161,233 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
         _ = send_task => {},
         _ = recv_task => {},
     }
+    let _ = close_tx.send(ControlMsg::ClearViewport { client });
 
     info!("WebSocket client disconnected");
 }
+
+/// What the send loop does with one item from the broadcast channel.
+enum Outbound {
+    Send(Message),
+    Skip,
+    Close,
+}
+
+/// Turn a broadcast item into the frame for this client. A client with a
+/// viewport gets its INIT, ANTS and PHEROMONE frames from the simulation
+/// directly, so the shared ones are skipped.
+async fn next_frame(
+    msg: Result<WsOut, broadcast::error::RecvError>,
+    viewport_rx: &watch::Receiver<Option<Viewport>>,
+    subscribed_rx: &watch::Receiver<Option<i32>>,
+    resync_tx: &ControlTx,
+) -> Outbound {
+    let viewport = *viewport_rx.borrow();
+    match msg {
+        Ok(WsOut::Binary(bytes)) => {
+            let own = [
+                binary::FRAME_INIT,
+                binary::FRAME_ANTS,
+                binary::FRAME_PHEROMONE,
+            ];
+            if viewport.is_some() && bytes.first().is_some_and(|t| own.contains(t)) {
+                return Outbound::Skip;
+            }
+            Outbound::Send(Message::Binary(bytes.as_ref().clone()))
+        }
+        Ok(WsOut::Text(text)) => Outbound::Send(Message::Text(text.as_ref().clone())),
+        // client too slow for the broadcast buffer: skip missed frames and
+        // resync from a fresh INIT, in case one of them was an INIT
+        Err(broadcast::error::RecvError::Lagged(n)) => {
+            warn!("WebSocket client lagged, skipped {} frames; resyncing", n);
+            let Some(simulation_id) = *subscribed_rx.borrow() else {
+                return Outbound::Skip;
+            };
+            let (reply, init) = oneshot::channel();
+            if resync_tx
+                .send(ControlMsg::FullState {
+                    simulation_id,
+                    viewport,
+                    reply,
+                })
+                .is_err()
+            {
+                return Outbound::Skip;
+            }
+            // dropped if the simulation moved on; later frames still flow
+            let Ok(init) = init.await else {
+                return Outbound::Skip;
+            };
+            Outbound::Send(Message::Binary(init.as_ref().clone()))
+        }
+        Err(broadcast::error::RecvError::Closed) => Outbound::Close,
+    }
+}
+
+/// Ask the simulation to change a config field; returns the `error` frame
+/// to send back if it refused.
+async fn set_config(
+    control_tx: &ControlTx,
+    simulation_id: i32,
+    key: String,
+    value: f32,
+) -> Option<String> {
+    let (reply, result) = oneshot::channel();
+    let _ = control_tx.send(ControlMsg::SetConfig {
+        simulation_id,
+        key,
+        value,
+        reply,
+    });
+    let error = match result.await {
+        Ok(Ok(())) => return None,
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => format!("simulation {} is not running", simulation_id),
+    };
+    Some(error_frame(&error))
+}
+
+fn error_frame(message: &str) -> String {
+    serde_json::json!({ "type": "error", "message": message }).to_string()
+}
+
+/// Translate a client command into a control message for the simulation the
+/// client is subscribed to.
+pub fn sim_command(msg: ClientMessage, simulation_id: i32) -> Option<ControlMsg> {
+    match msg {
+        ClientMessage::Pause => Some(ControlMsg::Pause { simulation_id }),
+        ClientMessage::Resume => Some(ControlMsg::Resume { simulation_id }),
+        ClientMessage::SetSpeed { speed } => Some(ControlMsg::SetSpeed {
+            simulation_id,
+            speed,
+        }),
+        ClientMessage::SpawnFood { x, y, amount } => Some(ControlMsg::SpawnFood {
+            simulation_id,
+            x,
+            y,
+            amount,
+        }),
+        ClientMessage::Subscribe { .. }
+        | ClientMessage::SetViewport { .. }
+        | ClientMessage::ClearViewport
+        | ClientMessage::SetConfig { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+    use crate::simulation::SimulationState;
+
+    #[tokio::test]
+    async fn a_lagging_client_is_resynced_with_a_fresh_init() {
+        let sim = SimulationState::new(SimConfig::small());
+        let init = Arc::new(binary::encode_init(&sim, None));
+        let (control_tx, control_rx) = std::sync::mpsc::channel::<ControlMsg>();
+        let sim_thread = std::thread::spawn({
+            let init = init.clone();
+            move || match control_rx.recv().unwrap() {
+                ControlMsg::FullState {
+                    simulation_id,
+                    viewport,
+                    reply,
+                } => {
+                    assert_eq!(simulation_id, 3);
+                    assert_eq!(viewport, None);
+                    let _ = reply.send(init);
+                }
+                _ => panic!("expected a FullState request"),
+            }
+        });
+
+        // a one-frame buffer overflows on the second send
+        let (tx, mut rx) = broadcast::channel(1);
+        for _ in 0..3 {
+            tx.send(WsOut::Text(Arc::new("stats".into()))).ok();
+        }
+        let (_viewport_tx, viewport_rx) = watch::channel(None);
+        let (_subscribed_tx, subscribed_rx) = watch::channel(Some(3));
+
+        let msg = rx.recv().await;
+        assert!(matches!(msg, Err(broadcast::error::RecvError::Lagged(2))));
+        let step = next_frame(msg, &viewport_rx, &subscribed_rx, &control_tx).await;
+        let Outbound::Send(Message::Binary(frame)) = step else {
+            panic!("no INIT sent after lagging");
+        };
+        assert_eq!(frame, *init);
+        sim_thread.join().unwrap();
+
+        // the newest frame still follows
+        let msg = rx.recv().await;
+        let step = next_frame(msg, &viewport_rx, &subscribed_rx, &control_tx).await;
+        assert!(matches!(step, Outbound::Send(Message::Text(t)) if t == "stats"));
+    }
+
+    #[tokio::test]
+    async fn a_client_with_a_viewport_skips_the_shared_world_frames() {
+        let sim = SimulationState::new(SimConfig::small());
+        let (control_tx, _control_rx) = std::sync::mpsc::channel::<ControlMsg>();
+        let (_subscribed_tx, subscribed_rx) = watch::channel(Some(3));
+        let (viewport_tx, viewport_rx) = watch::channel(None);
+        let frames = [
+            binary::encode_init(&sim, None),
+            binary::encode_ants(&sim, None),
+            binary::encode_pheromones(&sim, None),
+            binary::encode_food(&sim),
+        ];
+        let sent = |step: Outbound| matches!(step, Outbound::Send(_));
+
+        for frame in &frames {
+            let msg = Ok(WsOut::Binary(Arc::new(frame.clone())));
+            assert!(sent(
+                next_frame(msg, &viewport_rx, &subscribed_rx, &control_tx).await
+            ));
+        }
+        viewport_tx
+            .send(Some(Viewport {
+                min_x: 0.0,
+                min_y: 0.0,
+                max_x: 100.0,
+                max_y: 100.0,
+            }))
+            .unwrap();
+        let mut passed = Vec::new();
+        for frame in &frames {
+            let msg = Ok(WsOut::Binary(Arc::new(frame.clone())));
+            if sent(next_frame(msg, &viewport_rx, &subscribed_rx, &control_tx).await) {
+                passed.push(frame[0]);
+            }
+        }
+        assert_eq!(passed, [binary::FRAME_FOOD]);
+    }
+
+    #[tokio::test]
+    async fn set_config_updates_the_field_or_answers_with_an_error() {
+        let (control_tx, control_rx) = std::sync::mpsc::channel::<ControlMsg>();
+        let sim_thread = std::thread::spawn(move || {
+            let mut sim = SimulationState::new(SimConfig::small());
+            let id = sim.config.simulation_id;
+            for msg in control_rx {
+                assert!(crate::apply_command(&mut sim, msg).is_none());
+            }
+            (id, sim.config.pheromone_evaporation)
+        });
+        let id = SimConfig::small().simulation_id;
+
+        let ok = set_config(&control_tx, id, "pheromone_evaporation".into(), 0.9).await;
+        assert_eq!(ok, None);
+
+        let error = set_config(&control_tx, id, "world_width".into(), 10.0)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_str(&error).unwrap();
+        assert_eq!(error["type"], "error");
+        assert!(error["message"].as_str().unwrap().contains("world_width"));
+
+        drop(control_tx);
+        let (sim_id, evaporation) = sim_thread.join().unwrap();
+        assert_eq!(sim_id, id);
+        assert_eq!(evaporation, 0.9);
+    }
+}
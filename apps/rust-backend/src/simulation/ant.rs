@@ -11,6 +11,11 @@ pub const ROLE_WORKER: u8 = 0;
 pub const ROLE_SCOUT: u8 = 1;
 pub const ROLE_SOLDIER: u8 = 2;
 
+/// What a worker is currently assigned to (see `SimulationState::assign_tasks`).
+/// Scouts and soldiers always keep `TASK_FORAGER`.
+pub const TASK_FORAGER: u8 = 0;
+pub const TASK_NURSE: u8 = 1;
+
 /// Structure-of-Arrays storage for all ant data.
 /// Each field is a parallel Vec indexed by the ant's slot.
 #[derive(Clone, Serialize, Deserialize)]
@@ -29,11 +34,19 @@ pub struct AntStorage {
     pub state: Vec<AntState>,
     pub colony_id: Vec<u32>,
     pub ant_type: Vec<u8>,
+    pub task: Vec<u8>,
 
     pub cargo: Vec<f32>,
     /// Richness [0,1] of the food source this ant's cargo came from, captured at
     /// pickup. Scales the strength of the recruitment trail laid while returning.
     pub cargo_quality: Vec<f32>,
+    /// Nutrition per unit of the current cargo.
+    pub cargo_nutrition: Vec<f32>,
+    /// Tick the current cargo was picked up, for trip-time stats.
+    pub pickup_tick: Vec<u64>,
+    /// Cargo taken from a rival's store: already counted when the rival
+    /// collected it, so it is stored but not counted again.
+    pub cargo_looted: Vec<bool>,
     pub energy: Vec<f32>,
     pub health: Vec<f32>,
     pub age: Vec<u64>,
@@ -42,6 +55,17 @@ pub struct AntStorage {
     pub home_vec_y: Vec<f32>,
     pub wander_angle: Vec<f32>,
     pub levy_cooldown: Vec<u32>,
+    /// Food source id a recruited worker is heading for, -1 = none.
+    pub target_food: Vec<i32>,
+    /// Cell of the colony's `ExploreGrid` a scout is heading for, -1 = none.
+    pub explore_target: Vec<i32>,
+    /// Rival colony id a soldier is off to raid, -1 = none.
+    pub raid_target: Vec<i32>,
+    /// Pheromone sensitivity gained from experience (see `sensitivity`).
+    pub sensitivity_gain: Vec<f32>,
+    /// Waypoints of the outbound trip, oldest first; after a pickup, the
+    /// remaining way home (consumed from the back).
+    pub path: Vec<Vec<(f32, f32)>>,
 }
 
 impl AntStorage {
@@ -59,8 +83,12 @@ impl AntStorage {
             state: Vec::new(),
             colony_id: Vec::new(),
             ant_type: Vec::new(),
+            task: Vec::new(),
             cargo: Vec::new(),
             cargo_quality: Vec::new(),
+            cargo_nutrition: Vec::new(),
+            pickup_tick: Vec::new(),
+            cargo_looted: Vec::new(),
             energy: Vec::new(),
             health: Vec::new(),
             age: Vec::new(),
@@ -68,6 +96,11 @@ impl AntStorage {
             home_vec_y: Vec::new(),
             wander_angle: Vec::new(),
             levy_cooldown: Vec::new(),
+            target_food: Vec::new(),
+            explore_target: Vec::new(),
+            raid_target: Vec::new(),
+            sensitivity_gain: Vec::new(),
+            path: Vec::new(),
         }
     }
 
@@ -94,8 +127,12 @@ impl AntStorage {
         self.state.push(AntState::Foraging);
         self.colony_id.push(colony_id);
         self.ant_type.push(ant_type);
+        self.task.push(TASK_FORAGER);
         self.cargo.push(0.0);
         self.cargo_quality.push(0.0);
+        self.cargo_nutrition.push(0.0);
+        self.pickup_tick.push(0);
+        self.cargo_looted.push(false);
         self.energy.push(100.0);
         self.health.push(100.0);
         self.age.push(0);
@@ -103,6 +140,11 @@ impl AntStorage {
         self.home_vec_y.push(0.0);
         self.wander_angle.push(0.0);
         self.levy_cooldown.push(0);
+        self.target_food.push(-1);
+        self.explore_target.push(-1);
+        self.raid_target.push(-1);
+        self.sensitivity_gain.push(0.0);
+        self.path.push(Vec::new());
 
         id
     }
@@ -120,8 +162,12 @@ impl AntStorage {
         self.state.swap_remove(i);
         self.colony_id.swap_remove(i);
         self.ant_type.swap_remove(i);
+        self.task.swap_remove(i);
         self.cargo.swap_remove(i);
         self.cargo_quality.swap_remove(i);
+        self.cargo_nutrition.swap_remove(i);
+        self.pickup_tick.swap_remove(i);
+        self.cargo_looted.swap_remove(i);
         self.energy.swap_remove(i);
         self.health.swap_remove(i);
         self.age.swap_remove(i);
@@ -129,6 +175,38 @@ impl AntStorage {
         self.home_vec_y.swap_remove(i);
         self.wander_angle.swap_remove(i);
         self.levy_cooldown.swap_remove(i);
+        self.target_food.swap_remove(i);
+        self.explore_target.swap_remove(i);
+        self.raid_target.swap_remove(i);
+        self.sensitivity_gain.swap_remove(i);
+        self.path.swap_remove(i);
+    }
+
+    /// Multiplier on ant `i`'s pheromone sensor distance: an inborn
+    /// `1 ± spread`, fixed by its id, plus what it has learned.
+    pub fn sensitivity(&self, i: usize, spread: f32) -> f32 {
+        let h = self.id[i].wrapping_mul(0x9E37_79B1);
+        let inborn = (h >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+        (1.0 + inborn * spread + self.sensitivity_gain[i]).max(0.1)
+    }
+}
+
+/// Parse a role name as used by the HTTP API and the `ant_types` table.
+pub fn role_from_name(name: &str) -> Option<u8> {
+    match name {
+        "worker" => Some(ROLE_WORKER),
+        "scout" => Some(ROLE_SCOUT),
+        "soldier" => Some(ROLE_SOLDIER),
+        _ => None,
+    }
+}
+
+/// Inverse of `role_from_name`; unknown roles read as "worker".
+pub fn role_name(role: u8) -> &'static str {
+    match role {
+        ROLE_SCOUT => "scout",
+        ROLE_SOLDIER => "soldier",
+        _ => "worker",
     }
 }
 
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::geometry::dist_sq;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Colony {
     pub id: u32,
@@ -8,4 +10,212 @@ pub struct Colony {
     pub radius: f32,
     pub food_stored: f32,
     pub color_hue: u16,
+    /// Relative worker/scout/soldier weights for newly spawned members.
+    pub role_mix: [f32; 3],
+    /// 0 (timid) to 1 (belligerent); 0.5 leaves the role mix and patrol
+    /// radius as configured. Founded colonies inherit it.
+    pub aggression: f32,
+    /// Living members, recounted every tick.
+    pub population: usize,
+    /// Consecutive ticks spent at capacity with surplus food; founds a new
+    /// colony once it reaches `colony_founding_ticks`.
+    pub crowded_ticks: u32,
+    /// Set while stored food is below one foraging window of upkeep and
+    /// cleared once it is back to twice that (see `update_need`). A colony
+    /// in need nurses less and sends idle workers to known food.
+    pub needs_food: bool,
+    /// Ids of food sources reported by returning scouts, oldest first.
+    pub known_food: Vec<u32>,
+    pub foraging: ForagingStats,
+    /// Eggs and larvae, oldest first.
+    pub brood: Vec<Brood>,
+    pub mortality: Mortality,
+    pub explored: ExploreGrid,
+}
+
+/// Coarse cells of the world a colony's members have walked through.
+/// Scouts with nothing better to do head for the nearest cell nobody has
+/// visited yet, so exploration spreads out instead of circling the nest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ExploreGrid {
+    cell_size: f32,
+    w: usize,
+    h: usize,
+    visited: Vec<bool>,
+}
+
+impl ExploreGrid {
+    /// A grid of `cell_size` cells over the world (empty if `cell_size` is
+    /// 0). Cells whose center is solid count as visited from the start.
+    pub fn new(
+        world_width: f32,
+        world_height: f32,
+        cell_size: f32,
+        is_solid: impl Fn(f32, f32) -> bool,
+    ) -> Self {
+        if cell_size <= 0.0 {
+            return Self::default();
+        }
+        let w = (world_width / cell_size).ceil() as usize;
+        let h = (world_height / cell_size).ceil() as usize;
+        let mut grid = Self {
+            cell_size,
+            w,
+            h,
+            visited: vec![false; w * h],
+        };
+        for idx in 0..w * h {
+            let (cx, cy) = grid.center(idx);
+            grid.visited[idx] = is_solid(cx, cy);
+        }
+        grid
+    }
+
+    pub fn visit(&mut self, x: f32, y: f32) {
+        if self.visited.is_empty() || x < 0.0 || y < 0.0 {
+            return;
+        }
+        let (gx, gy) = ((x / self.cell_size) as usize, (y / self.cell_size) as usize);
+        if gx < self.w && gy < self.h {
+            self.visited[gy * self.w + gx] = true;
+        }
+    }
+
+    pub fn is_visited(&self, idx: usize) -> bool {
+        self.visited.get(idx).copied().unwrap_or(true)
+    }
+
+    pub fn center(&self, idx: usize) -> (f32, f32) {
+        (
+            ((idx % self.w) as f32 + 0.5) * self.cell_size,
+            ((idx / self.w) as f32 + 0.5) * self.cell_size,
+        )
+    }
+
+    /// Closest cell to (x, y) not yet visited, if any are left.
+    pub fn nearest_unvisited(&self, x: f32, y: f32) -> Option<usize> {
+        (0..self.visited.len())
+            .filter(|&idx| !self.visited[idx])
+            .min_by(|&a, &b| {
+                dist_sq(self.center(a), (x, y)).total_cmp(&dist_sq(self.center(b), (x, y)))
+            })
+    }
+}
+
+/// Why an ant died.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    /// Ran out of energy: the colony couldn't feed it.
+    Starvation,
+    OldAge,
+    /// Lost its health inside a hazard region.
+    Hazard,
+}
+
+/// Deaths among a colony's members since it was founded, by cause.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mortality {
+    pub starvation: u64,
+    pub old_age: u64,
+    pub hazard: u64,
+}
+
+impl Mortality {
+    pub fn record(&mut self, cause: DeathCause) {
+        match cause {
+            DeathCause::Starvation => self.starvation += 1,
+            DeathCause::OldAge => self.old_age += 1,
+            DeathCause::Hazard => self.hazard += 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BroodStage {
+    Egg,
+    Larva,
+}
+
+/// A developing ant. Eggs mature on their own; larvae only grow on ticks a
+/// nurse feeds them and hatch into an adult of `role`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Brood {
+    pub stage: BroodStage,
+    pub role: u8,
+    /// Ticks spent developing in the current stage.
+    pub ticks: u32,
+}
+
+impl Colony {
+    /// `role_mix` with the soldier weight scaled by aggression, from none at
+    /// 0 to double at 1.
+    pub fn spawn_mix(&self) -> [f32; 3] {
+        let [worker, scout, soldier] = self.role_mix;
+        [worker, scout, soldier * 2.0 * self.aggression]
+    }
+
+    /// How far from the nest soldiers patrol: `base` at neutral aggression,
+    /// half of it at 0 and one and a half times it at 1.
+    pub fn patrol_radius(&self, base: f32) -> f32 {
+        base * (0.5 + self.aggression)
+    }
+
+    /// Raise or clear `needs_food` from the current store; returns true if
+    /// it changed.
+    pub fn update_need(&mut self, upkeep_per_ant: f32) -> bool {
+        let reserve = self.population as f32 * upkeep_per_ant * FORAGING_WINDOW as f32;
+        let needs = if self.needs_food {
+            self.food_stored < reserve * 2.0
+        } else {
+            self.food_stored < reserve
+        };
+        let changed = needs != self.needs_food;
+        self.needs_food = needs;
+        changed
+    }
+
+    pub fn brood_count(&self, stage: BroodStage) -> usize {
+        self.brood.iter().filter(|b| b.stage == stage).count()
+    }
+}
+
+/// Length of the window the foraging figures are measured over, in ticks.
+pub const FORAGING_WINDOW: u64 = 1_000;
+
+/// Foraging efficiency of a colony. Deposits are tallied over a window of
+/// `FORAGING_WINDOW` ticks; the published figures describe the last
+/// completed window.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ForagingStats {
+    window_food: f32,
+    window_trips: u32,
+    window_trip_ticks: u64,
+    /// Food deposited per 1000 ticks.
+    pub deposit_rate: f32,
+    /// Mean ticks from pickup to deposit, `None` if no trip completed.
+    pub avg_trip_ticks: Option<f32>,
+    /// Non-soldier members currently outside the nest, recounted every tick.
+    pub foragers: usize,
+}
+
+impl ForagingStats {
+    pub fn record_food(&mut self, food: f32) {
+        self.window_food += food;
+    }
+
+    pub fn record_trip(&mut self, trip_ticks: u64) {
+        self.window_trips += 1;
+        self.window_trip_ticks += trip_ticks;
+    }
+
+    /// Publish the current window's figures and start a new one.
+    pub fn roll_window(&mut self) {
+        self.deposit_rate = self.window_food * 1_000.0 / FORAGING_WINDOW as f32;
+        self.avg_trip_ticks = (self.window_trips > 0)
+            .then(|| self.window_trip_ticks as f32 / self.window_trips as f32);
+        self.window_food = 0.0;
+        self.window_trips = 0;
+        self.window_trip_ticks = 0;
+    }
 }
@@ -7,4 +7,31 @@ pub struct FoodSource {
     pub y: f32,
     pub amount: f32,
     pub max_amount: f32,
+    /// 0.1 (obvious) to 1.0 (well hidden): scales down the per-tick chance
+    /// that an ant in sight range notices this source.
+    pub discovery_difficulty: f32,
+    /// Colony food gained per unit carried home.
+    pub nutrition: f32,
+    /// Index of the `food_kinds` entry this source was drawn as; `None` when
+    /// no kinds are configured.
+    #[serde(default)]
+    pub kind: Option<usize>,
+}
+
+/// A kind of food placed in the world, drawn in proportion to `weight` among
+/// the configured kinds (`[[food_kinds]]`), so rich kinds can be made rare.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoodKind {
+    pub name: String,
+    pub weight: f32,
+    /// Nutrition of every source of this kind (see `FoodSource::nutrition`).
+    pub nutrition: f32,
+}
+
+/// A depleted source waiting to grow back (see `food_respawn_ticks`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DormantFood {
+    pub source: FoodSource,
+    pub respawn_tick: u64,
 }
@@ -0,0 +1,105 @@
+//! Distance, circle and segment tests shared by the simulation. Points are
+//! `(x, y)` tuples, as everywhere else in the engine.
+
+use std::f32::consts::PI;
+
+pub fn dist_sq(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)
+}
+
+/// True if `p` lies inside the circle or on its edge.
+pub fn point_in_circle(p: (f32, f32), center: (f32, f32), radius: f32) -> bool {
+    dist_sq(p, center) <= radius * radius
+}
+
+/// Point of segment `a`-`b` nearest to `p`; `a` if the segment is a point.
+pub fn closest_point_on_segment(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = abx * abx + aby * aby;
+    let t = if len_sq > 0.0 {
+        (((p.0 - a.0) * abx + (p.1 - a.1) * aby) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (a.0 + abx * t, a.1 + aby * t)
+}
+
+pub fn distance_to_segment(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    dist_sq(p, closest_point_on_segment(p, a, b)).sqrt()
+}
+
+/// True if segment `a`-`b` touches the circle, including a segment lying
+/// entirely inside it.
+pub fn segment_circle_intersects(
+    a: (f32, f32),
+    b: (f32, f32),
+    center: (f32, f32),
+    radius: f32,
+) -> bool {
+    point_in_circle(closest_point_on_segment(center, a, b), center, radius)
+}
+
+/// Axis-aligned rectangle in world space; points on its edges count as
+/// inside.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl Rect {
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+}
+
+/// Normalize an angle to [-PI, PI].
+pub fn normalize_angle(angle: f32) -> f32 {
+    let mut a = angle;
+    while a > PI {
+        a -= 2.0 * PI;
+    }
+    while a < -PI {
+        a += 2.0 * PI;
+    }
+    a
+}
+
+/// Signed turn from angle `from` to angle `to`, in [-PI, PI].
+pub fn angle_diff(from: f32, to: f32) -> f32 {
+    normalize_angle(to - from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_circle_intersection() {
+        let c = (0.0, 0.0);
+        // crosses the circle
+        assert!(segment_circle_intersects((-10.0, 1.0), (10.0, 1.0), c, 5.0));
+        // grazes it
+        assert!(segment_circle_intersects((-10.0, 5.0), (10.0, 5.0), c, 5.0));
+        // passes by
+        assert!(!segment_circle_intersects(
+            (-10.0, 6.0),
+            (10.0, 6.0),
+            c,
+            5.0
+        ));
+        // stops short of it
+        assert!(!segment_circle_intersects(
+            (-20.0, 0.0),
+            (-6.0, 0.0),
+            c,
+            5.0
+        ));
+        // lies inside it
+        assert!(segment_circle_intersects((-1.0, 0.0), (1.0, 0.0), c, 5.0));
+        // a point segment
+        assert!(segment_circle_intersects((3.0, 0.0), (3.0, 0.0), c, 5.0));
+    }
+}
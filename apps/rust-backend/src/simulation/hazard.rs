@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::geometry::{point_in_circle, segment_circle_intersects};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HazardKind {
+    Flood,
+    Fire,
+    Pesticide,
+}
+
+impl HazardKind {
+    /// Wire code used in the INIT frame.
+    pub fn code(self) -> u8 {
+        match self {
+            HazardKind::Flood => 0,
+            HazardKind::Fire => 1,
+            HazardKind::Pesticide => 2,
+        }
+    }
+}
+
+/// A circular region that hurts every ant inside it. Hazards are fixed for
+/// the lifetime of a simulation and come from the config (`[[hazards]]`) or
+/// the `hazards` override of a simulation row.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    /// Health lost per second inside the region (ants start with 100).
+    pub damage: f32,
+}
+
+impl Hazard {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        point_in_circle((x, y), (self.x, self.y), self.radius)
+    }
+
+    /// True if walking straight from `a` to `b` passes through the hazard.
+    pub fn crosses(&self, a: (f32, f32), b: (f32, f32)) -> bool {
+        segment_circle_intersects(a, b, (self.x, self.y), self.radius)
+    }
+}
@@ -1,6 +1,8 @@
 pub mod ant;
 pub mod colony;
 pub mod food;
+pub mod geometry;
+pub mod hazard;
 pub mod pheromone;
 pub mod steering;
 pub mod terrain;
@@ -9,13 +11,26 @@ use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
 
-use crate::config::SimConfig;
-use ant::{speed_for_role, AntState, AntStorage, ROLE_SCOUT, ROLE_SOLDIER, ROLE_WORKER};
-use colony::Colony;
-use food::FoodSource;
+use crate::config::{ColonyLayout, SimConfig};
+use ant::{
+    speed_for_role, AntState, AntStorage, ROLE_SCOUT, ROLE_SOLDIER, ROLE_WORKER, TASK_FORAGER,
+    TASK_NURSE,
+};
+use colony::{
+    Brood, BroodStage, Colony, DeathCause, ExploreGrid, ForagingStats, Mortality, FORAGING_WINDOW,
+};
+use food::{DormantFood, FoodSource};
+use geometry::{angle_diff, dist_sq, normalize_angle, point_in_circle, Rect};
 use pheromone::{PheromoneField, PheromoneType};
 use terrain::Terrain;
 
+/// Bounds for `SimulationState::simulation_speed`.
+pub const MIN_SPEED: f32 = 0.1;
+pub const MAX_SPEED: f32 = 8.0;
+
+/// Cargo this close to `ant_carry_capacity` counts as a full load.
+const FULL_LOAD_SLACK: f32 = 1e-4;
+
 /// Result of one ant's movement computation (produced in parallel, applied sequentially).
 #[derive(Clone, Copy)]
 struct AntMove {
@@ -30,6 +45,13 @@ struct AntMove {
     picked_food: i32,
     /// index of the colony the ant deposited cargo at, -1 = none
     deposited: i32,
+    /// index of the rival colony whose nest a raider reached, -1 = none
+    raided: i32,
+    /// id of a food source the ant just noticed, -1 = none
+    discovered: i32,
+    /// reached the next waypoint of its way home
+    waypoint_reached: bool,
+    explore_target: i32,
 }
 
 pub struct SimulationState {
@@ -37,20 +59,38 @@ pub struct SimulationState {
     pub ants: AntStorage,
     pub colonies: Vec<Colony>,
     pub food_sources: Vec<FoodSource>,
+    /// Depleted sources waiting to grow back; they take no part in sensing
+    /// or pickup and are not sent to clients until they return.
+    pub dormant_food: Vec<DormantFood>,
     pub pheromones: PheromoneField,
     pub terrain: Terrain,
     pub tick_count: u64,
     pub total_food_collected: f32,
+    /// Id for the next food source placed. Never reused, so a client or ant
+    /// holding the id of a removed source can't confuse it with a new one.
+    pub next_food_id: u32,
+    /// Set by clients; a paused simulation keeps serving its current state.
+    pub paused: bool,
+    /// Ticks advanced per real tick period (see `set_speed`).
+    pub simulation_speed: f32,
     move_scratch: Vec<AntMove>,
     rng: SmallRng,
+    /// Set when colonies or food sources were added/removed, so clients
+    /// need a fresh INIT frame.
+    world_changed: bool,
+    /// Set once `max_ants` blocks a spawn and cleared when ants plus brood
+    /// fall well below it, so the cap is warned about once per episode
+    /// rather than every time a death frees a slot.
+    at_ant_cap: bool,
 }
 
 impl SimulationState {
     pub fn new(config: SimConfig) -> Self {
         let mut rng = SmallRng::seed_from_u64(config.terrain_seed ^ 0xA5A5_5A5A);
 
-        let cx = config.world_width / 2.0;
-        let cy = config.world_height / 2.0;
+        let mut nests = colony_layout(&config);
+        separate_nests(&mut nests, &config);
+        let (cx, cy) = nests[0];
 
         // ── terrain ────────────────────────────────────────────────────
         let mut terrain = Terrain::generate(
@@ -61,40 +101,69 @@ impl SimulationState {
             config.terrain_density,
             config.terrain_smooth_iterations,
         );
-        terrain.carve_circle(cx, cy, config.colony_radius * 2.5);
+        for &(x, y) in &nests {
+            terrain.carve_circle(x, y, config.colony_radius * 2.5);
+            terrain.carve_corridor(cx, cy, x, y, config.colony_radius * 0.5);
+        }
         terrain.fill_unreachable(cx, cy);
 
-        let colonies = vec![Colony {
-            id: 0,
-            x: cx,
-            y: cy,
-            radius: config.colony_radius,
-            food_stored: 200.0,
-            color_hue: 30,
-        }];
+        let mut colonies: Vec<Colony> = nests
+            .iter()
+            .enumerate()
+            .map(|(k, &(x, y))| Colony {
+                id: k as u32,
+                x,
+                y,
+                radius: config.colony_radius,
+                food_stored: config.colony_initial_food,
+                color_hue: (30 + 360 * k / nests.len()) as u16 % 360,
+                role_mix: config.role_mix,
+                aggression: config.colony_aggression.clamp(0.0, 1.0),
+                population: 0,
+                crowded_ticks: 0,
+                needs_food: false,
+                known_food: Vec::new(),
+                foraging: ForagingStats::default(),
+                brood: Vec::new(),
+                mortality: Mortality::default(),
+                explored: explore_grid(&config, &terrain),
+            })
+            .collect();
 
         // ── food sources on open, reachable ground ────────────────────
-        let mut food_sources = Vec::with_capacity(config.food_source_count);
-        for i in 0..config.food_source_count {
-            let pos = terrain.random_open_position(
-                &mut rng,
-                Some((cx, cy, config.food_min_distance_from_colony)),
-            );
+        let food_count = config.initial_food_sources();
+        let mut food_sources = Vec::with_capacity(food_count);
+        let min_d = config.food_min_distance_from_colony;
+        let clear_of_nests = |&(x, y): &(f32, f32)| {
+            nests
+                .iter()
+                .all(|&nest| dist_sq(nest, (x, y)) >= min_d * min_d)
+        };
+        for i in 0..food_count {
+            let pos = (0..64).find_map(|_| {
+                terrain
+                    .random_open_position(&mut rng, Some((cx, cy, min_d)))
+                    .filter(clear_of_nests)
+            });
             // relax the distance constraint if the world is too cramped
             let pos = pos.or_else(|| terrain.random_open_position(&mut rng, None));
             if let Some((fx, fy)) = pos {
+                let (kind, nutrition) = roll_food_kind(&mut rng, &config);
                 food_sources.push(FoodSource {
                     id: i as u32,
                     x: fx,
                     y: fy,
                     amount: config.food_per_source,
                     max_amount: config.food_per_source,
+                    discovery_difficulty: rng.gen_range(0.1..=1.0),
+                    nutrition,
+                    kind,
                 });
             }
         }
         // small clearings around food so it stays accessible
         for fs in &food_sources {
-            terrain.carve_circle(fs.x, fs.y, 20.0);
+            terrain.carve_circle(fs.x, fs.y, config.food_clearing_radius);
         }
 
         // ── pheromones (terrain-aware) ─────────────────────────────────
@@ -106,42 +175,241 @@ impl SimulationState {
         pheromones.build_blocked_mask(&terrain);
         // Anchor the home gradient to the nest(s) as a static radial field, so
         // returning is true navigation rather than following a density blob.
-        let colony_positions: Vec<(f32, f32)> = colonies.iter().map(|c| (c.x, c.y)).collect();
+        let colony_positions: Vec<(u32, f32, f32)> =
+            colonies.iter().map(|c| (c.id, c.x, c.y)).collect();
         pheromones.seed_home_field(&colony_positions);
+        if !config.hazards.is_empty() {
+            pheromones.seed_danger_field(&config.hazards, config.hazard_warning_distance);
+        }
 
-        // ── initial ants: 70% worker, 20% scout, 10% soldier ───────────
+        // ── initial ants, shared out between colonies in their role mix ──
+        let initial = match config.colony_starting_population {
+            0 => config.initial_ant_count,
+            n => n.saturating_mul(colonies.len()),
+        };
         let mut ants = AntStorage::new();
-        for k in 0..config.initial_ant_count {
-            let role = if k % 10 < 7 {
-                ROLE_WORKER
-            } else if k % 10 < 9 {
-                ROLE_SCOUT
-            } else {
-                ROLE_SOLDIER
-            };
+        for n in 0..initial.min(config.max_ants) {
+            let ci = n % colonies.len();
+            let c = &colonies[ci];
+            let role = random_role(&mut rng, &c.spawn_mix());
             let angle: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
             let r: f32 = rng.gen_range(0.0..config.colony_radius * 0.8);
-            let ax = cx + angle.cos() * r;
-            let ay = cy + angle.sin() * r;
+            let ax = c.x + angle.cos() * r;
+            let ay = c.y + angle.sin() * r;
             let heading: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
             let spd = speed_for_role(role, config.ant_max_speed);
-            ants.add(ax, ay, 0, role, spd, heading);
+            ants.add(ax, ay, c.id, role, spd, heading);
+            colonies[ci].population += 1;
         }
 
-        Self {
+        let initial_speed = config.initial_speed;
+        let mut state = Self {
             config,
             ants,
             colonies,
             food_sources,
+            dormant_food: Vec::new(),
             pheromones,
             terrain,
             tick_count: 0,
             total_food_collected: 0.0,
+            next_food_id: food_count as u32,
+            paused: false,
+            simulation_speed: 1.0,
             move_scratch: Vec::new(),
             rng,
+            world_changed: false,
+            at_ant_cap: false,
+        };
+        state.set_speed(initial_speed);
+        state
+    }
+
+    /// Set the speed multiplier, clamped to `MIN_SPEED..=MAX_SPEED`. A speed
+    /// of 0 or less pauses instead, keeping the old speed for `Resume`.
+    /// Non-finite values are ignored.
+    pub fn set_speed(&mut self, speed: f32) {
+        if !speed.is_finite() {
+            return;
+        }
+        if speed <= 0.0 {
+            self.paused = true;
+        } else {
+            self.simulation_speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        }
+    }
+
+    /// Add the ant's position to its outbound path once it has walked
+    /// `ant_waypoint_spacing` from the last waypoint. A full path is thinned
+    /// first, and only loses its oldest point if that doesn't free room.
+    fn record_waypoint(&mut self, i: usize) {
+        let (x, y) = (self.ants.pos_x[i], self.ants.pos_y[i]);
+        let spacing = self.config.ant_waypoint_spacing;
+        if let Some(&(lx, ly)) = self.ants.path[i].last() {
+            if dist_sq((lx, ly), (x, y)) < spacing * spacing {
+                return;
+            }
+        }
+        if self.ants.path[i].len() >= self.config.ant_path_waypoints {
+            self.ants.path[i] = self.simplified_path(i);
+            if self.ants.path[i].len() >= self.config.ant_path_waypoints {
+                self.ants.path[i].remove(0);
+            }
+        }
+        self.ants.path[i].push((x, y));
+    }
+
+    /// The ant's path with points dropped where a straight, wall-free
+    /// shortcut stays within `ant_path_tolerance`.
+    fn simplified_path(&self, i: usize) -> Vec<(f32, f32)> {
+        let terrain = &self.terrain;
+        let hazards = &self.config.hazards;
+        steering::simplify_path(
+            &self.ants.path[i],
+            self.config.ant_path_tolerance,
+            &|a, b| {
+                terrain.segment_clear(a.0, a.1, b.0, b.1)
+                    && !hazards.iter().any(|h| h.crosses(a, b))
+            },
+        )
+    }
+
+    /// Cargo of ant `i` as a fraction of `ant_carry_capacity`.
+    fn load_fraction(&self, i: usize) -> f32 {
+        if self.config.ant_carry_capacity > 0.0 {
+            self.ants.cargo[i] / self.config.ant_carry_capacity
+        } else {
+            1.0
+        }
+    }
+
+    /// Room left in ant `i`'s load; unlimited when `ant_carry_capacity` is 0.
+    fn carry_room(&self, i: usize) -> f32 {
+        if self.config.ant_carry_capacity > 0.0 {
+            self.config.ant_carry_capacity - self.ants.cargo[i]
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    /// Turn a laden ant for home, shortening the way it came.
+    fn head_home(&mut self, i: usize) {
+        self.ants.state[i] = AntState::Returning;
+        if !self.ants.path[i].is_empty() {
+            self.ants.path[i] = self.simplified_path(i);
         }
     }
 
+    fn note_ant_cap(&mut self, blocked: bool) {
+        if blocked {
+            if !self.at_ant_cap {
+                tracing::warn!(
+                    "Simulation {} reached max_ants ({}); no more ants will spawn",
+                    self.config.simulation_id,
+                    self.config.max_ants
+                );
+            }
+            self.at_ant_cap = true;
+        } else {
+            if self.ants_and_brood() < self.config.max_ants / 10 * 9 {
+                self.at_ant_cap = false;
+            }
+        }
+    }
+
+    /// Living ants plus the brood that will hatch into more; `max_ants`
+    /// caps this sum.
+    fn ants_and_brood(&self) -> usize {
+        self.ants.count + self.colonies.iter().map(|c| c.brood.len()).sum::<usize>()
+    }
+
+    /// Returns true (once) if the world layout changed since the last call.
+    pub fn take_world_changed(&mut self) -> bool {
+        std::mem::take(&mut self.world_changed)
+    }
+
+    /// Place a new food source at a world position. Returns its id, or `None`
+    /// if the position is outside the world or inside solid rock.
+    pub fn spawn_food(&mut self, x: f32, y: f32, amount: f32) -> Option<u32> {
+        if self.terrain.is_solid_at(x, y) {
+            return None;
+        }
+        let id = self.next_food_id;
+        self.next_food_id += 1;
+        let amount = amount.max(1.0);
+        let (kind, nutrition) = roll_food_kind(&mut self.rng, &self.config);
+        self.food_sources.push(FoodSource {
+            id,
+            x,
+            y,
+            amount,
+            max_amount: amount,
+            discovery_difficulty: self.rng.gen_range(0.1..=1.0),
+            nutrition,
+            kind,
+        });
+        self.world_changed = true;
+        Some(id)
+    }
+
+    /// Slots of the ants within `radius` of a world position, nearest first.
+    pub fn ants_near(&self, x: f32, y: f32, radius: f32) -> Vec<usize> {
+        let r2 = radius * radius;
+        let ants = &self.ants;
+        let dist2 = |i: usize| {
+            let dx = ants.pos_x[i] - x;
+            let dy = ants.pos_y[i] - y;
+            dx * dx + dy * dy
+        };
+        let mut near: Vec<usize> = (0..ants.count).filter(|&i| dist2(i) <= r2).collect();
+        near.sort_by(|&a, &b| dist2(a).total_cmp(&dist2(b)));
+        near
+    }
+
+    /// Slots of the ants inside `rect` (edges included), in storage order.
+    pub fn ants_in_rect(&self, rect: &Rect) -> Vec<usize> {
+        (0..self.ants.count)
+            .filter(|&i| rect.contains((self.ants.pos_x[i], self.ants.pos_y[i])))
+            .collect()
+    }
+
+    /// Indices into `food_sources` of the sources inside `rect` (edges
+    /// included).
+    pub fn food_in_rect(&self, rect: &Rect) -> Vec<usize> {
+        (0..self.food_sources.len())
+            .filter(|&j| rect.contains((self.food_sources[j].x, self.food_sources[j].y)))
+            .collect()
+    }
+
+    /// Drop up to `count` ants at a world position, joining the nearest colony.
+    /// Without an explicit role they follow the usual worker/scout/soldier mix.
+    /// Returns the number actually spawned (limited by `max_ants`, brood
+    /// included).
+    pub fn spawn_ants_at(&mut self, x: f32, y: f32, count: usize, role: Option<u8>) -> usize {
+        if self.terrain.is_solid_at(x, y) {
+            return 0;
+        }
+        let Some(colony) = self
+            .colonies
+            .iter()
+            .min_by(|a, b| dist_sq((a.x, a.y), (x, y)).total_cmp(&dist_sq((b.x, b.y), (x, y))))
+        else {
+            return 0;
+        };
+        let cid = colony.id;
+        let mix = colony.spawn_mix();
+
+        let n = count.min(self.config.max_ants.saturating_sub(self.ants_and_brood()));
+        self.note_ant_cap(n < count);
+        for _ in 0..n {
+            let role = role.unwrap_or_else(|| random_role(&mut self.rng, &mix));
+            let spd = speed_for_role(role, self.config.ant_max_speed);
+            let heading: f32 = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            self.ants.add(x, y, cid, role, spd, heading);
+        }
+        n
+    }
+
     pub fn tick(&mut self) {
         let dt = 1.0 / self.config.tick_rate;
         self.tick_count += 1;
@@ -159,7 +427,19 @@ impl SimulationState {
 
             (0..ants.count)
                 .into_par_iter()
-                .map(|i| compute_ant_move(i, dt, tick, cfg, terrain, pheromones, food_sources, colonies, ants))
+                .map(|i| {
+                    compute_ant_move(
+                        i,
+                        dt,
+                        tick,
+                        cfg,
+                        terrain,
+                        pheromones,
+                        food_sources,
+                        colonies,
+                        ants,
+                    )
+                })
                 .collect_into_vec(scratch);
         }
 
@@ -167,30 +447,119 @@ impl SimulationState {
         for i in 0..self.ants.count {
             let m = self.move_scratch[i];
 
+            if m.discovered >= 0 {
+                self.ants.target_food[i] = m.discovered;
+            }
             if m.picked_food >= 0 {
                 let j = m.picked_food as usize;
-                if self.food_sources[j].amount >= 1.0 {
-                    self.food_sources[j].amount -= 1.0;
-                    self.ants.cargo[i] = 1.0;
+                let held = self.ants.cargo[i];
+                // no pickup fills past capacity
+                let room = self.carry_room(i);
+                if self.food_sources[j].amount > 0.0 && room > FULL_LOAD_SLACK {
+                    let take = self.food_sources[j]
+                        .amount
+                        .min(self.config.food_pickup_amount.max(0.01))
+                        .min(room);
+                    self.food_sources[j].amount -= take;
+                    // rounding can leave a topped-up load a hair short of full
+                    let cargo = if room - take <= FULL_LOAD_SLACK {
+                        self.config.ant_carry_capacity
+                    } else {
+                        held + take
+                    };
+                    self.ants.cargo[i] = cargo;
                     // Capture source richness (fraction remaining) so the
                     // recruitment trail laid on the way back is strong for rich
                     // sources and fades as the source is depleted.
                     let fs = &self.food_sources[j];
+                    self.ants.cargo_nutrition[i] =
+                        (self.ants.cargo_nutrition[i] * held + fs.nutrition * take) / cargo;
                     self.ants.cargo_quality[i] = if fs.max_amount > 0.0 {
                         (fs.amount / fs.max_amount).clamp(0.0, 1.0)
                     } else {
                         0.0
                     };
-                    self.ants.state[i] = AntState::Returning;
+                    if held <= 0.0 {
+                        self.ants.pickup_tick[i] = self.tick_count;
+                    }
+                    self.ants.target_food[i] = -1;
+                    if self.ants.ant_type[i] == ROLE_SCOUT {
+                        let id = fs.id;
+                        self.report_food(self.ants.colony_id[i], id);
+                    }
+                    if self.load_fraction(i) >= self.config.ant_return_fill.min(1.0) {
+                        self.head_home(i);
+                    }
+                } else if room <= FULL_LOAD_SLACK {
+                    self.head_home(i);
                 }
             } else if m.deposited >= 0 {
                 let c = m.deposited as usize;
-                self.colonies[c].food_stored += self.ants.cargo[i];
-                self.total_food_collected += self.ants.cargo[i];
-                self.ants.cargo[i] = 0.0;
-                self.ants.state[i] = AntState::Foraging;
-                self.ants.home_vec_x[i] = 0.0;
-                self.ants.home_vec_y[i] = 0.0;
+                let cargo = self.ants.cargo[i];
+                let unload = if self.config.ant_unload_rate > 0.0 {
+                    cargo.min(self.config.ant_unload_rate)
+                } else {
+                    cargo
+                };
+                let looted = self.ants.cargo_looted[i];
+                self.colonies[c].food_stored += unload * self.ants.cargo_nutrition[i];
+                if !looted {
+                    self.colonies[c].foraging.record_food(unload);
+                    self.total_food_collected += unload;
+                }
+                self.ants.cargo[i] -= unload;
+                if unload >= cargo {
+                    if !looted {
+                        let gain = &mut self.ants.sensitivity_gain[i];
+                        *gain = (*gain + self.config.ant_sensitivity_learning)
+                            .min(self.config.ant_sensitivity_max_gain);
+                        self.colonies[c]
+                            .foraging
+                            .record_trip(self.tick_count - self.ants.pickup_tick[i]);
+                    }
+                    self.ants.target_food[i] =
+                        recruit_target(&self.colonies[c], self.ants.ant_type[i], &mut self.rng);
+                    self.ants.cargo[i] = 0.0;
+                    self.ants.cargo_looted[i] = false;
+                    self.ants.state[i] = AntState::Foraging;
+                    self.ants.home_vec_x[i] = 0.0;
+                    self.ants.home_vec_y[i] = 0.0;
+                    self.ants.path[i].clear();
+                }
+            } else if m.raided >= 0 {
+                let r = m.raided as usize;
+                let take = self.colonies[r].food_stored.min(self.carry_room(i));
+                self.ants.raid_target[i] = -1;
+                if take > 0.0 {
+                    self.colonies[r].food_stored -= take;
+                    // the store is already counted in nutrition; loot lays no trail
+                    self.ants.cargo[i] = take;
+                    self.ants.cargo_nutrition[i] = 1.0;
+                    self.ants.cargo_quality[i] = 0.0;
+                    self.ants.cargo_looted[i] = true;
+                    self.ants.state[i] = AntState::Returning;
+                    self.ants.pickup_tick[i] = self.tick_count;
+                    if !self.ants.path[i].is_empty() {
+                        self.ants.path[i] = self.simplified_path(i);
+                    }
+                    tracing::debug!(
+                        "Ant {} of colony {} stole {:.1} food from colony {}",
+                        self.ants.id[i],
+                        self.ants.colony_id[i],
+                        take,
+                        self.colonies[r].id
+                    );
+                }
+            }
+            // a partial load that can't be topped up in time is brought home
+            if self.ants.state[i] == AntState::Foraging
+                && self.ants.cargo[i] > 0.0
+                && self.tick_count - self.ants.pickup_tick[i] >= self.config.ant_top_up_ticks
+            {
+                self.head_home(i);
+            }
+            if m.waypoint_reached {
+                self.ants.path[i].pop();
             }
 
             self.ants.home_vec_x[i] += m.x - self.ants.pos_x[i];
@@ -202,21 +571,43 @@ impl SimulationState {
             self.ants.heading[i] = m.heading;
             self.ants.wander_angle[i] = m.wander_angle;
             self.ants.levy_cooldown[i] = m.levy_cooldown;
+            self.ants.explore_target[i] = m.explore_target;
+            if self.ants.ant_type[i] != ROLE_SOLDIER {
+                let cid = self.ants.colony_id[i];
+                if let Some(c) = self.colonies.iter_mut().find(|c| c.id == cid) {
+                    c.explored.visit(m.x, m.y);
+                }
+            }
+            if self.config.ant_path_waypoints > 0 && self.ants.state[i] == AntState::Foraging {
+                self.record_waypoint(i);
+            }
 
             // Only ants returning with food lay a recruitment (food) trail, and
             // its strength scales with the richness of the source they found.
             // Foraging wanderers lay nothing — the home gradient is the static
             // field seeded at startup, not something emitted while wandering.
-            if self.ants.state[i] == AntState::Returning {
-                self.pheromones.deposit(
+            // Laying trail costs energy, and a tired ant lays a weaker one.
+            // Nothing is laid while unloading in the nest.
+            if self.ants.state[i] == AntState::Returning
+                && m.deposited < 0
+                && self.ants.cargo_quality[i] > 0.0
+            {
+                let min_energy = self.config.pheromone_min_energy.max(1e-3);
+                let vigor = (self.ants.energy[i] / min_energy).clamp(0.0, 1.0);
+                self.pheromones.deposit_food(
                     m.x,
                     m.y,
-                    PheromoneType::Food,
-                    self.config.pheromone_food_deposit * self.ants.cargo_quality[i],
+                    m.heading,
+                    self.config.pheromone_food_deposit * self.ants.cargo_quality[i] * vigor,
+                    self.ants.colony_id[i],
                 );
+                self.ants.energy[i] -= self.config.pheromone_energy_cost;
             }
         }
 
+        self.remove_depleted_food();
+        self.respawn_food();
+
         // ── phase 3: colony upkeep (trophallaxis), aging, and death ────
         // Workers no longer starve individually on a foraging trip. Instead the
         // colony feeds its population from stored food each tick: while the
@@ -225,12 +616,19 @@ impl SimulationState {
         // die of old age once they pass their (slightly randomized) lifespan.
         let upkeep = self.config.colony_upkeep_per_ant;
         let mut colony_food: Vec<f32> = self.colonies.iter().map(|c| c.food_stored).collect();
+        let mut population = vec![0usize; self.colonies.len()];
+        let mut foragers = vec![0usize; self.colonies.len()];
+        let mut nurses = vec![0usize; self.colonies.len()];
 
         let mut i = 0;
         while i < self.ants.count {
             self.ants.age[i] += 1;
 
-            let fed = match self.colonies.iter().position(|c| c.id == self.ants.colony_id[i]) {
+            let colony = self
+                .colonies
+                .iter()
+                .position(|c| c.id == self.ants.colony_id[i]);
+            let fed = match colony {
                 Some(ci) if colony_food[ci] >= upkeep => {
                     colony_food[ci] -= upkeep;
                     true
@@ -238,73 +636,778 @@ impl SimulationState {
                 _ => false,
             };
 
+            let in_nest = colony.is_some_and(|ci| {
+                let c = &self.colonies[ci];
+                point_in_circle(
+                    (self.ants.pos_x[i], self.ants.pos_y[i]),
+                    (c.x, c.y),
+                    c.radius,
+                )
+            });
+
+            // the first of the checks below to run an ant out sets the cause
+            let mut cause = None;
             if fed {
+                // resting in the nest restores energy faster than being fed
+                // out in the field
+                let rest = if in_nest {
+                    self.config.ant_nest_rest_recovery
+                } else {
+                    0.0
+                };
                 self.ants.energy[i] =
-                    (self.ants.energy[i] + self.config.ant_feed_recovery).min(100.0);
+                    (self.ants.energy[i] + self.config.ant_feed_recovery + rest).min(100.0);
             } else {
                 self.ants.energy[i] -= self.config.ant_starve_damage;
+                if self.ants.energy[i] <= 0.0 {
+                    cause = Some(DeathCause::Starvation);
+                }
+            }
+
+            for h in &self.config.hazards {
+                if h.contains(self.ants.pos_x[i], self.ants.pos_y[i]) {
+                    self.ants.health[i] -= h.damage * dt;
+                }
+            }
+            if self.ants.health[i] <= 0.0 {
+                cause = cause.or(Some(DeathCause::Hazard));
             }
 
             let lifespan = self.config.ant_lifespan_ticks
                 + (self.ants.id[i] as u64 % self.config.ant_lifespan_variation.max(1));
+            if self.ants.age[i] >= lifespan {
+                cause = cause.or(Some(DeathCause::OldAge));
+            }
 
-            if self.ants.energy[i] <= 0.0 || self.ants.age[i] >= lifespan {
+            if let Some(cause) = cause {
+                if let Some(ci) = colony {
+                    self.colonies[ci].mortality.record(cause);
+                }
                 self.ants.remove(i);
                 // swap_remove: re-process the swapped-in element at i
             } else {
+                if let Some(ci) = colony {
+                    population[ci] += 1;
+                    if self.ants.ant_type[i] != ROLE_SOLDIER && !in_nest {
+                        foragers[ci] += 1;
+                    }
+                    let nursing = if self.config.worker_nurse_share > 0.0 {
+                        self.ants.task[i] == TASK_NURSE
+                    } else {
+                        self.ants.ant_type[i] == ROLE_WORKER
+                            && self.ants.state[i] == AntState::Foraging
+                    };
+                    if in_nest && nursing {
+                        nurses[ci] += 1;
+                    }
+                }
                 i += 1;
             }
         }
 
+        let keep = 1.0 - self.config.colony_food_spoilage.clamp(0.0, 1.0);
         for (ci, c) in self.colonies.iter_mut().enumerate() {
-            c.food_stored = colony_food[ci];
+            c.food_stored = colony_food[ci] * keep;
+            c.population = population[ci];
+            c.foraging.foragers = foragers[ci];
+            if c.update_need(upkeep) {
+                tracing::debug!(
+                    "Colony {} {} food",
+                    c.id,
+                    if c.needs_food {
+                        "needs"
+                    } else {
+                        "no longer needs"
+                    }
+                );
+            }
+        }
+        if self.tick_count.is_multiple_of(FORAGING_WINDOW) {
+            for c in &mut self.colonies {
+                c.foraging.roll_window();
+            }
         }
 
-        self.spawn_ants();
+        self.tend_brood(&nurses);
+        self.assign_tasks();
+        self.launch_raids();
+        self.lay_eggs();
+        self.found_colonies();
 
         self.pheromones.evaporate(self.config.pheromone_evaporation);
-        if self.tick_count % self.config.pheromone_diffusion_interval == 0 {
-            self.pheromones.diffuse(self.config.pheromone_diffusion_rate);
+        if self.config.queen_pheromone_range > 0.0 {
+            for c in self.colonies.iter().filter(|c| c.population > 0) {
+                self.pheromones
+                    .emit_queen(c.x, c.y, self.config.queen_pheromone_range, 1.0);
+            }
+        }
+        if self
+            .tick_count
+            .is_multiple_of(self.config.pheromone_diffusion_interval)
+        {
+            self.pheromones
+                .diffuse(self.config.pheromone_diffusion_rate);
+            if self.config.wind_speed > 0.0 {
+                let drift =
+                    self.config.wind_speed * dt * self.config.pheromone_diffusion_interval as f32;
+                self.pheromones.advect(
+                    self.config.wind_direction.cos() * drift,
+                    self.config.wind_direction.sin() * drift,
+                );
+            }
+        }
+    }
+
+    /// Each colony's queen lays a batch of eggs every spawn interval while
+    /// there is food and room (brood counts toward the colony and global
+    /// population caps).
+    fn lay_eggs(&mut self) {
+        if !self
+            .tick_count
+            .is_multiple_of(self.config.colony_spawn_interval)
+        {
+            return;
+        }
+        let mut total = self.ants_and_brood();
+        let (mut laid, mut capped) = (false, false);
+        for colony in &mut self.colonies {
+            let size = colony.population + colony.brood.len();
+            let room = self.config.colony_max_population.saturating_sub(size);
+            let wanted = affordable_ant_count(
+                colony.food_stored,
+                &self.config,
+                size,
+                self.config.colony_spawn_batch.min(room),
+            );
+            let n = wanted.min(self.config.max_ants.saturating_sub(total));
+            capped |= n < wanted;
+            if n == 0 {
+                continue;
+            }
+            laid = true;
+            total += n;
+            colony.food_stored -= egg_cost(&self.config, size, n);
+            for _ in 0..n {
+                let role = random_role(&mut self.rng, &colony.spawn_mix());
+                colony.brood.push(Brood {
+                    stage: BroodStage::Egg,
+                    role,
+                    ticks: 0,
+                });
+            }
+        }
+        if laid || capped {
+            self.note_ant_cap(capped);
+        }
+    }
+
+    /// Every `colony_labor_interval` ticks, split each colony's workers
+    /// between foraging and nursing: enough nurses for its larvae, up to
+    /// `worker_nurse_share` of the workers (half that while the colony needs
+    /// food). New nurses are drawn from unladen foragers nearest the nest;
+    /// surplus nurses go back to foraging. A colony in need also sends its
+    /// remaining idle foragers without a target to food its scouts reported.
+    fn assign_tasks(&mut self) {
+        let cfg = &self.config;
+        if !self.tick_count.is_multiple_of(cfg.colony_labor_interval) {
+            return;
+        }
+        let mut nurses: Vec<Vec<usize>> = vec![Vec::new(); self.colonies.len()];
+        let mut idle: Vec<Vec<usize>> = vec![Vec::new(); self.colonies.len()];
+        let mut workers = vec![0usize; self.colonies.len()];
+        for i in 0..self.ants.count {
+            if self.ants.ant_type[i] != ROLE_WORKER {
+                continue;
+            }
+            let Some(ci) = self
+                .colonies
+                .iter()
+                .position(|c| c.id == self.ants.colony_id[i])
+            else {
+                continue;
+            };
+            workers[ci] += 1;
+            if self.ants.task[i] == TASK_NURSE {
+                nurses[ci].push(i);
+            } else if self.ants.state[i] == AntState::Foraging && self.ants.cargo[i] <= 0.0 {
+                idle[ci].push(i);
+            }
+        }
+
+        for (ci, c) in self.colonies.iter().enumerate() {
+            let needed = c
+                .brood_count(BroodStage::Larva)
+                .div_ceil(cfg.brood_larvae_per_nurse.max(1));
+            let share = if c.needs_food {
+                cfg.worker_nurse_share * 0.5
+            } else {
+                cfg.worker_nurse_share
+            };
+            let target = needed.min((workers[ci] as f32 * share) as usize);
+
+            if nurses[ci].len() > target {
+                for &i in &nurses[ci][target..] {
+                    self.ants.task[i] = TASK_FORAGER;
+                }
+            } else {
+                let dist = |i: usize| dist_sq((self.ants.pos_x[i], self.ants.pos_y[i]), (c.x, c.y));
+                let candidates = &mut idle[ci];
+                candidates.sort_by(|&a, &b| dist(a).total_cmp(&dist(b)));
+                for &i in candidates.iter().take(target - nurses[ci].len()) {
+                    self.ants.task[i] = TASK_NURSE;
+                    self.ants.target_food[i] = -1;
+                }
+            }
+
+            if c.needs_food {
+                for &i in &idle[ci] {
+                    if self.ants.task[i] == TASK_FORAGER && self.ants.target_food[i] < 0 {
+                        self.ants.target_food[i] = recruit_target(c, ROLE_WORKER, &mut self.rng);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every `colony_labor_interval` ticks, colonies at least
+    /// `raid_min_aggression` aggressive send `raid_party_share` of their idle
+    /// soldiers to the richest rival holding `raid_min_rival_food` or more.
+    /// Raiders walk into the rival nest, take up to a full load from its
+    /// store and carry it home; raids on a rival that stops being the target
+    /// are called off.
+    fn launch_raids(&mut self) {
+        let cfg = &self.config;
+        if !self.tick_count.is_multiple_of(cfg.colony_labor_interval) {
+            return;
+        }
+        let targets: Vec<Option<u32>> = self
+            .colonies
+            .iter()
+            .map(|c| {
+                if c.population == 0 || c.aggression < cfg.raid_min_aggression {
+                    return None;
+                }
+                self.colonies
+                    .iter()
+                    .filter(|r| r.id != c.id && r.food_stored >= cfg.raid_min_rival_food)
+                    .max_by(|a, b| a.food_stored.total_cmp(&b.food_stored))
+                    .map(|r| r.id)
+            })
+            .collect();
+
+        // raids on a rival that is no longer the target are called off
+        let mut idle: Vec<Vec<usize>> = vec![Vec::new(); self.colonies.len()];
+        for i in 0..self.ants.count {
+            if self.ants.ant_type[i] != ROLE_SOLDIER || self.ants.state[i] != AntState::Foraging {
+                continue;
+            }
+            let Some(ci) = self
+                .colonies
+                .iter()
+                .position(|c| c.id == self.ants.colony_id[i])
+            else {
+                continue;
+            };
+            let raid = self.ants.raid_target[i];
+            if raid >= 0 && targets[ci] != Some(raid as u32) {
+                self.ants.raid_target[i] = -1;
+            }
+            if self.ants.raid_target[i] < 0 {
+                idle[ci].push(i);
+            }
+        }
+        for (ci, target) in targets.into_iter().enumerate() {
+            let Some(target) = target else {
+                continue;
+            };
+            let party = (idle[ci].len() as f32 * cfg.raid_party_share).ceil() as usize;
+            for &i in idle[ci].iter().take(party) {
+                self.ants.raid_target[i] = target as i32;
+            }
+            if party > 0 {
+                tracing::debug!(
+                    "Colony {} sends {} soldiers to raid colony {}",
+                    self.colonies[ci].id,
+                    party,
+                    target
+                );
+            }
+        }
+    }
+
+    /// Advance the brood: eggs turn into larvae after `brood_egg_ticks`;
+    /// larvae develop only on ticks a nurse in the nest feeds them from the
+    /// store, and hatch into adults once grown.
+    fn tend_brood(&mut self, nurses: &[usize]) {
+        let cfg = &self.config;
+        let ration = cfg.brood_larva_food / cfg.brood_larva_ticks.max(1) as f32;
+        let mut room = cfg.max_ants.saturating_sub(self.ants.count);
+        let mut hatched: Vec<(usize, u8)> = Vec::new();
+        let mut held_back = false;
+
+        for (ci, colony) in self.colonies.iter_mut().enumerate() {
+            let mut feedings = nurses[ci] * cfg.brood_larvae_per_nurse;
+            let food = &mut colony.food_stored;
+            colony.brood.retain_mut(|b| match b.stage {
+                BroodStage::Egg => {
+                    b.ticks += 1;
+                    if b.ticks >= cfg.brood_egg_ticks {
+                        b.stage = BroodStage::Larva;
+                        b.ticks = 0;
+                    }
+                    true
+                }
+                BroodStage::Larva => {
+                    if feedings > 0 && *food >= ration {
+                        feedings -= 1;
+                        *food -= ration;
+                        b.ticks += 1;
+                    }
+                    if b.ticks < cfg.brood_larva_ticks {
+                        true
+                    } else if room > 0 {
+                        room -= 1;
+                        hatched.push((ci, b.role));
+                        false
+                    } else {
+                        held_back = true;
+                        true
+                    }
+                }
+            });
+        }
+
+        if held_back || !hatched.is_empty() {
+            self.note_ant_cap(held_back);
+        }
+        for (ci, role) in hatched {
+            let c = &mut self.colonies[ci];
+            c.population += 1;
+            let spd = speed_for_role(role, self.config.ant_max_speed);
+            let angle: f32 = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            let offset: f32 = self.rng.gen_range(0.0..c.radius * 0.5);
+            let heading: f32 = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            self.ants.add(
+                c.x + angle.cos() * offset,
+                c.y + angle.sin() * offset,
+                c.id,
+                role,
+                spd,
+                heading,
+            );
+            let last = self.ants.count - 1;
+            self.ants.target_food[last] = recruit_target(c, role, &mut self.rng);
+        }
+    }
+
+    /// A scout found food: put it on its colony's board (evicting the oldest
+    /// report when full) so workers can be recruited to it.
+    fn report_food(&mut self, colony_id: u32, food_id: u32) {
+        let capacity = self.config.colony_known_food_capacity;
+        let Some(colony) = self.colonies.iter_mut().find(|c| c.id == colony_id) else {
+            return;
+        };
+        if capacity == 0 || colony.known_food.contains(&food_id) {
+            return;
+        }
+        if colony.known_food.len() >= capacity {
+            colony.known_food.remove(0);
+        }
+        colony.known_food.push(food_id);
+    }
+
+    /// Remove food sources that ran out, drop them from the colony boards and
+    /// call off workers still heading for them.
+    fn remove_depleted_food(&mut self) {
+        let mut depleted = Vec::new();
+        let respawn_ticks = self.config.food_respawn_ticks;
+        let fraction = self.config.food_respawn_fraction;
+        let respawn_tick = self.tick_count + respawn_ticks;
+        let dormant = &mut self.dormant_food;
+        self.food_sources.retain(|f| {
+            if f.amount > 0.0 {
+                return true;
+            }
+            depleted.push(f.id as i32);
+            if respawn_ticks > 0 && f.max_amount * fraction >= 1.0 {
+                dormant.push(DormantFood {
+                    source: f.clone(),
+                    respawn_tick,
+                });
+            }
+            false
+        });
+        if depleted.is_empty() {
+            return;
+        }
+        self.world_changed = true;
+        tracing::debug!("Food sources {:?} depleted", depleted);
+        for colony in &mut self.colonies {
+            colony
+                .known_food
+                .retain(|&id| !depleted.contains(&(id as i32)));
+        }
+        for target in &mut self.ants.target_food {
+            if depleted.contains(target) {
+                *target = -1;
+            }
         }
     }
 
-    fn spawn_ants(&mut self) {
-        if self.tick_count % self.config.colony_spawn_interval != 0 {
+    /// Bring back dormant sources whose cooldown is over, at a fraction of
+    /// their previous maximum.
+    fn respawn_food(&mut self) {
+        let tick = self.tick_count;
+        let fraction = self.config.food_respawn_fraction;
+        let mut respawned = Vec::new();
+        self.dormant_food.retain(|d| {
+            if d.respawn_tick > tick {
+                return true;
+            }
+            let mut source = d.source.clone();
+            source.max_amount *= fraction;
+            source.amount = source.max_amount;
+            respawned.push(source);
+            false
+        });
+        if respawned.is_empty() {
             return;
         }
-        for ci in 0..self.colonies.len() {
-            let mut budget = self.config.colony_spawn_batch;
-            while budget > 0
-                && self.ants.count < self.config.max_ants
-                && self.colonies[ci].food_stored >= self.config.colony_spawn_cost
+        self.world_changed = true;
+        tracing::debug!(
+            "Food sources {:?} respawned",
+            respawned.iter().map(|f| f.id).collect::<Vec<_>>()
+        );
+        self.food_sources.extend(respawned);
+    }
+
+    /// A colony that has been full and well-stocked for a while splits: a
+    /// group of its foraging workers emigrates with part of the food store to
+    /// found a new colony some distance away.
+    fn found_colonies(&mut self) {
+        let cfg = &self.config;
+        let mut founder = None;
+        for (ci, c) in self.colonies.iter_mut().enumerate() {
+            if c.population >= cfg.colony_max_population
+                && c.food_stored >= cfg.colony_founding_surplus
+            {
+                c.crowded_ticks += 1;
+                if c.crowded_ticks >= cfg.colony_founding_ticks && founder.is_none() {
+                    founder = Some(ci);
+                }
+            } else {
+                c.crowded_ticks = 0;
+            }
+        }
+        let Some(parent) = founder else { return };
+        self.colonies[parent].crowded_ticks = 0;
+
+        let Some((x, y)) = self.find_colony_site(parent) else {
+            tracing::warn!(
+                "Colony {} is crowded but found no site to settle",
+                self.colonies[parent].id
+            );
+            return;
+        };
+
+        let id = self.colonies.iter().map(|c| c.id + 1).max().unwrap_or(0);
+        let share = self.colonies[parent].food_stored * self.config.colony_founding_food_share;
+        self.colonies[parent].food_stored -= share;
+        let parent_id = self.colonies[parent].id;
+        let hue = (self.colonies[parent].color_hue + 137) % 360;
+
+        let mut emigrants = 0;
+        for i in 0..self.ants.count {
+            if emigrants == self.config.colony_founding_group {
+                break;
+            }
+            if self.ants.colony_id[i] == parent_id
+                && self.ants.ant_type[i] == ROLE_WORKER
+                && self.ants.state[i] == AntState::Foraging
             {
-                budget -= 1;
-                self.colonies[ci].food_stored -= self.config.colony_spawn_cost;
-
-                let cx = self.colonies[ci].x;
-                let cy = self.colonies[ci].y;
-                let cid = self.colonies[ci].id;
-
-                let r: f32 = self.rng.gen();
-                let role = if r < 0.7 {
-                    ROLE_WORKER
-                } else if r < 0.9 {
-                    ROLE_SCOUT
+                self.ants.colony_id[i] = id;
+                emigrants += 1;
+            }
+        }
+        self.colonies[parent].population -= emigrants;
+
+        self.colonies.push(Colony {
+            id,
+            x,
+            y,
+            radius: self.config.colony_radius,
+            food_stored: share,
+            color_hue: hue,
+            role_mix: self.colonies[parent].role_mix,
+            aggression: self.colonies[parent].aggression,
+            population: emigrants,
+            crowded_ticks: 0,
+            needs_food: false,
+            known_food: Vec::new(),
+            foraging: ForagingStats::default(),
+            brood: Vec::new(),
+            mortality: Mortality::default(),
+            explored: explore_grid(&self.config, &self.terrain),
+        });
+
+        self.terrain
+            .carve_circle(x, y, self.config.colony_radius * 2.5);
+        self.pheromones.build_blocked_mask(&self.terrain);
+        let colony_positions: Vec<(u32, f32, f32)> =
+            self.colonies.iter().map(|c| (c.id, c.x, c.y)).collect();
+        self.pheromones.seed_home_field(&colony_positions);
+        self.world_changed = true;
+
+        tracing::info!(
+            "Colony {} founded colony {} at ({:.0}, {:.0}) with {} workers",
+            parent_id,
+            id,
+            x,
+            y,
+            emigrants
+        );
+    }
+
+    /// Open ground at least `colony_founding_distance` (and `colony_spacing`)
+    /// from every colony, preferring sites near the parent.
+    fn find_colony_site(&mut self, parent: usize) -> Option<(f32, f32)> {
+        let min_d = self
+            .config
+            .colony_founding_distance
+            .max(self.config.colony_spacing);
+        let (px, py) = (self.colonies[parent].x, self.colonies[parent].y);
+        let mut best: Option<(f32, f32, f32)> = None;
+        for _ in 0..64 {
+            let (x, y) = self
+                .terrain
+                .random_open_position(&mut self.rng, Some((px, py, min_d)))?;
+            let clear = self
+                .colonies
+                .iter()
+                .all(|c| dist_sq((c.x, c.y), (x, y)) >= min_d * min_d);
+            let d2 = dist_sq((px, py), (x, y));
+            if clear && best.is_none_or(|(_, _, bd)| d2 < bd) {
+                best = Some((x, y, d2));
+            }
+        }
+        best.map(|(x, y, _)| (x, y))
+    }
+}
+
+/// Nest positions for the starting colonies. A single colony always sits in
+/// the world center.
+fn colony_layout(cfg: &SimConfig) -> Vec<(f32, f32)> {
+    let (w, h) = (cfg.world_width, cfg.world_height);
+    let n = cfg.colony_count.max(1);
+    if n == 1 {
+        return vec![(w / 2.0, h / 2.0)];
+    }
+    match cfg.colony_layout {
+        ColonyLayout::Line => (0..n)
+            .map(|k| (w * (k + 1) as f32 / (n + 1) as f32, h / 2.0))
+            .collect(),
+        ColonyLayout::Ring => {
+            let r = w.min(h) * 0.3;
+            (0..n)
+                .map(|k| {
+                    let a =
+                        std::f32::consts::TAU * k as f32 / n as f32 - std::f32::consts::FRAC_PI_2;
+                    (w / 2.0 + a.cos() * r, h / 2.0 + a.sin() * r)
+                })
+                .collect()
+        }
+        ColonyLayout::Grid => {
+            let cols = (n as f32).sqrt().ceil() as usize;
+            let rows = n.div_ceil(cols);
+            (0..n)
+                .map(|k| {
+                    let (col, row) = (k % cols, k / cols);
+                    (
+                        w * (col + 1) as f32 / (cols + 1) as f32,
+                        h * (row + 1) as f32 / (rows + 1) as f32,
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+/// Push apart nests closer than `colony_spacing`, keeping each clearing inside
+/// the world. Layouts that already fit are left untouched; if the world is too
+/// small to fit them all, the nests end up as evenly crowded as it allows.
+fn separate_nests(nests: &mut [(f32, f32)], cfg: &SimConfig) {
+    let spacing = cfg.colony_spacing;
+    let margin = (cfg.colony_radius * 2.5).min(cfg.world_width.min(cfg.world_height) / 2.0);
+    let clamp = |(x, y): (f32, f32)| {
+        (
+            x.clamp(margin, cfg.world_width - margin),
+            y.clamp(margin, cfg.world_height - margin),
+        )
+    };
+    for _ in 0..100 {
+        let mut moved = false;
+        for a in 0..nests.len() {
+            for b in a + 1..nests.len() {
+                let d = dist_sq(nests[a], nests[b]).sqrt();
+                // a hair short is fine; clamping at the edges can leave that
+                if d >= spacing - 0.5 {
+                    continue;
+                }
+                // coincident nests split along a fixed angle per pair
+                let (ux, uy) = if d > 1e-3 {
+                    ((nests[b].0 - nests[a].0) / d, (nests[b].1 - nests[a].1) / d)
                 } else {
-                    ROLE_SOLDIER
+                    let angle = (a * 7 + b) as f32;
+                    (angle.cos(), angle.sin())
                 };
-                let spd = speed_for_role(role, self.config.ant_max_speed);
+                let push = (spacing - d) / 2.0;
+                nests[a] = clamp((nests[a].0 - ux * push, nests[a].1 - uy * push));
+                nests[b] = clamp((nests[b].0 + ux * push, nests[b].1 + uy * push));
+                moved = true;
+            }
+        }
+        if !moved {
+            return;
+        }
+    }
+    tracing::warn!(
+        "The world is too small to keep {} nests {} apart",
+        nests.len(),
+        spacing
+    );
+}
+
+fn explore_grid(cfg: &SimConfig, terrain: &Terrain) -> ExploreGrid {
+    ExploreGrid::new(
+        cfg.world_width,
+        cfg.world_height,
+        cfg.scout_explore_cell,
+        |x, y| terrain.is_solid_at(x, y),
+    )
+}
 
-                let angle: f32 = self.rng.gen_range(0.0..std::f32::consts::TAU);
-                let offset: f32 = self.rng.gen_range(0.0..self.colonies[ci].radius * 0.5);
-                let ax = cx + angle.cos() * offset;
-                let ay = cy + angle.sin() * offset;
-                let heading: f32 = self.rng.gen_range(0.0..std::f32::consts::TAU);
+/// Kind and nutrition of a new food source: a kind drawn by rarity weight, or
+/// no kind and a roll from `food_nutrition` when none are configured.
+fn roll_food_kind(rng: &mut SmallRng, cfg: &SimConfig) -> (Option<usize>, f32) {
+    let weights = cfg.food_kinds.iter().map(|k| k.weight);
+    if let Some(i) = weighted_choice(rng, weights) {
+        return (Some(i), cfg.food_kinds[i].nutrition.max(0.0));
+    }
+    let [lo, hi] = cfg.food_nutrition;
+    (
+        None,
+        if hi > lo { rng.gen_range(lo..=hi) } else { lo }.max(0.0),
+    )
+}
 
-                self.ants.add(ax, ay, cid, role, spd, heading);
-            }
+/// Food the next `n` eggs of a colony of `size` (adults and brood) cost; see
+/// `colony_spawn_cost_growth`.
+fn egg_cost(cfg: &SimConfig, size: usize, n: usize) -> f32 {
+    let cost = cfg.colony_spawn_cost;
+    let growth = cfg.colony_spawn_cost_growth.max(0.0) / cfg.colony_max_population.max(1) as f32;
+    let n = n as f32;
+    // sum over k < n of cost * (1 + growth * (size + k))
+    cost * n + cost * growth * (n * size as f32 + n * (n - 1.0) / 2.0)
+}
+
+/// How many eggs a colony of `size` can pay for out of `resources`, up to `max`.
+fn affordable_ant_count(resources: f32, cfg: &SimConfig, size: usize, max: usize) -> usize {
+    let cost = cfg.colony_spawn_cost;
+    if cost <= 0.0 {
+        return max;
+    }
+    // the cast saturates: negative or NaN resources afford nothing
+    let flat = ((resources / cost).floor() as usize).min(max);
+    if cfg.colony_spawn_cost_growth <= 0.0 {
+        return flat;
+    }
+    // growing costs never buy more than the flat price would
+    (0..=flat)
+        .rev()
+        .find(|&n| egg_cost(cfg, size, n) <= resources)
+        .unwrap_or(0)
+}
+
+/// Index drawn with probability proportional to its weight, or `None` if the
+/// weights don't add up to anything positive (negative weights count as 0).
+fn weighted_choice(
+    rng: &mut SmallRng,
+    weights: impl Iterator<Item = f32> + Clone,
+) -> Option<usize> {
+    let total: f32 = weights.clone().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut r = rng.gen::<f32>() * total;
+    let mut last = 0;
+    for (i, w) in weights.enumerate() {
+        let w = w.max(0.0);
+        if w <= 0.0 {
+            continue;
+        }
+        if r < w {
+            return Some(i);
         }
+        r -= w;
+        last = i;
+    }
+    // rounding can leave r just past the end
+    Some(last)
+}
+
+/// Per-tick chance of ant `i` starting a levy flight, damped by how strong
+/// a trail of its colony it is standing on.
+fn levy_chance(cfg: &SimConfig, pheromones: &PheromoneField, ants: &AntStorage, i: usize) -> f32 {
+    let damping = cfg.levy_trail_damping.clamp(0.0, 1.0);
+    if damping <= 0.0 {
+        return cfg.levy_probability;
+    }
+    let confidence = pheromones.trail_confidence(
+        ants.pos_x[i],
+        ants.pos_y[i],
+        ants.colony_id[i],
+        cfg.rival_trail_weight,
+    );
+    cfg.levy_probability * (1.0 - damping * confidence)
+}
+
+/// How far ahead ant `i`'s pheromone sensors sit; scouts reach further, and
+/// every ant is scaled by its own sensitivity.
+fn sensor_distance(cfg: &SimConfig, ants: &AntStorage, i: usize) -> f32 {
+    let reach = if ants.ant_type[i] == ROLE_SCOUT {
+        cfg.scout_sensor_reach
+    } else {
+        1.0
+    };
+    cfg.ant_sensor_distance * reach * ants.sensitivity(i, cfg.ant_sensitivity_spread)
+}
+
+/// Direction of the summed steering. Steering that cancels out (or went
+/// non-finite) has no direction, and atan2 would read it as due east: keep
+/// `heading` instead.
+fn steer_heading(steer_x: f32, steer_y: f32, heading: f32) -> f32 {
+    let steer_len_sq = steer_x * steer_x + steer_y * steer_y;
+    if steer_len_sq > 1e-8 && steer_len_sq.is_finite() {
+        steer_y.atan2(steer_x)
+    } else {
+        heading
+    }
+}
+
+/// Roll a role for a newly spawned ant from relative worker/scout/soldier
+/// weights. An all-zero mix yields workers.
+fn random_role(rng: &mut SmallRng, mix: &[f32; 3]) -> u8 {
+    const ROLES: [u8; 3] = [ROLE_WORKER, ROLE_SCOUT, ROLE_SOLDIER];
+    weighted_choice(rng, mix.iter().copied()).map_or(ROLE_WORKER, |i| ROLES[i])
+}
+
+/// Food source a worker leaving the nest is sent to: a random entry from its
+/// colony's board, or -1 to forage freely.
+fn recruit_target(colony: &Colony, role: u8, rng: &mut SmallRng) -> i32 {
+    if role != ROLE_WORKER || colony.known_food.is_empty() {
+        return -1;
     }
+    colony.known_food[rng.gen_range(0..colony.known_food.len())] as i32
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -325,9 +1428,13 @@ fn compute_ant_move(
     let role = ants.ant_type[i];
     let state = ants.state[i];
 
-    // deterministic per-ant, per-tick RNG (cheap, no shared state)
+    // deterministic per-ant, per-tick RNG (cheap, no shared state); the
+    // simulation seed goes in too, so runs differ between seeds but repeat
+    // exactly under the same one
     let mut rng = SmallRng::seed_from_u64(
-        tick.wrapping_mul(0x517C_C1B7_2722_0A95) ^ (ants.id[i] as u64).wrapping_mul(0x2545_F491_4F6C_DD1D),
+        tick.wrapping_mul(0x517C_C1B7_2722_0A95)
+            ^ (ants.id[i] as u64).wrapping_mul(0x2545_F491_4F6C_DD1D)
+            ^ cfg.terrain_seed.wrapping_mul(0x9E37_79B9_7F4A_7C15),
     );
 
     let mut m = AntMove {
@@ -340,17 +1447,28 @@ fn compute_ant_move(
         levy_cooldown: ants.levy_cooldown[i],
         picked_food: -1,
         deposited: -1,
+        raided: -1,
+        discovered: -1,
+        waypoint_reached: false,
+        explore_target: ants.explore_target[i],
     };
 
     // ── proximity events (detected here, applied sequentially) ────────
     let mut eff_state = state;
-    match state {
+    if state == AntState::Foraging && ants.raid_target[i] >= 0 && ants.cargo[i] <= 0.0 {
+        if let Some(j) = colonies.iter().position(|c| {
+            c.id as i32 == ants.raid_target[i] && point_in_circle((x, y), (c.x, c.y), c.radius)
+        }) {
+            m.raided = j as i32;
+        }
+    }
+    match state {
+        // nurses don't pick up food on their way back to the nest, and a
+        // raider that reached its target loots the store instead
+        AntState::Foraging if ants.task[i] == TASK_NURSE || m.raided >= 0 => {}
         AntState::Foraging => {
             let pickup_r_sq = cfg.ant_pickup_radius * cfg.ant_pickup_radius;
             for (j, fs) in food_sources.iter().enumerate() {
-                if fs.amount < 1.0 {
-                    continue;
-                }
                 let dx = fs.x - x;
                 let dy = fs.y - y;
                 if dx * dx + dy * dy < pickup_r_sq {
@@ -361,11 +1479,18 @@ fn compute_ant_move(
             }
         }
         AntState::Returning => {
+            let own = colonies.iter().position(|c| c.id == ants.colony_id[i]);
+            // cargo goes to the ant's own nest; rival nests are walked through
             for (j, c) in colonies.iter().enumerate() {
-                let dx = c.x - x;
-                let dy = c.y - y;
-                if dx * dx + dy * dy < c.radius * c.radius {
+                if own.is_some_and(|o| o != j) {
+                    continue;
+                }
+                if point_in_circle((x, y), (c.x, c.y), c.radius) {
                     m.deposited = j as i32;
+                    if cfg.ant_unload_rate > 0.0 && ants.cargo[i] > cfg.ant_unload_rate {
+                        // still unloading: stand still in the nest
+                        return m;
+                    }
                     eff_state = AntState::Foraging;
                     m.heading = rng.gen_range(0.0..std::f32::consts::TAU);
                     m.wander_angle = 0.0;
@@ -375,49 +1500,100 @@ fn compute_ant_move(
         }
     }
 
-    // ── levy flight (foraging wanderers only) ──────────────────────────
-    if eff_state == AntState::Foraging && m.levy_cooldown == 0 && rng.gen::<f32>() < 0.003 {
+    // ── levy flight (foraging wanderers only, rarer on a good trail) ───
+    if eff_state == AntState::Foraging
+        && ants.task[i] != TASK_NURSE
+        && m.levy_cooldown == 0
+        && rng.gen::<f32>() < levy_chance(cfg, pheromones, ants, i)
+    {
         m.heading = rng.gen_range(0.0..std::f32::consts::TAU);
         m.levy_cooldown = cfg.levy_cooldown_ticks;
     }
     if m.levy_cooldown > 0 {
         m.levy_cooldown -= 1;
     }
-    let levy_active = m.levy_cooldown > cfg.levy_cooldown_ticks.saturating_sub(30);
-    let speed_mult = if levy_active { cfg.levy_speed_boost } else { 1.0 };
-    let speed = ants.speed[i] * speed_mult;
+    let levy_active =
+        m.levy_cooldown > cfg.levy_cooldown_ticks.saturating_sub(cfg.levy_boost_ticks);
+    let speed_mult = if levy_active {
+        cfg.levy_speed_boost
+    } else {
+        1.0
+    };
+    let load = if cfg.ant_carry_capacity > 0.0 {
+        (ants.cargo[i] / cfg.ant_carry_capacity).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let load_mult = 1.0 - (1.0 - cfg.ant_laden_speed.clamp(0.0, 1.0)) * load;
+    let speed = ants.speed[i] * speed_mult * load_mult;
 
     // ── steering ───────────────────────────────────────────────────────
     let (mut steer_x, mut steer_y) = match eff_state {
         AntState::Foraging => steer_foraging(
-            i, x, y, m.heading, role, cfg, pheromones, food_sources, colonies, ants,
-            &mut m.wander_angle, &mut rng,
+            i,
+            x,
+            y,
+            m.heading,
+            dt,
+            role,
+            cfg,
+            pheromones,
+            food_sources,
+            colonies,
+            ants,
+            &mut m.wander_angle,
+            &mut m.discovered,
+            &mut m.explore_target,
+            &mut rng,
         ),
         AntState::Returning => steer_returning(
-            i, x, y, m.heading, cfg, pheromones, ants, &mut m.wander_angle, &mut rng,
+            i,
+            x,
+            y,
+            m.heading,
+            dt,
+            cfg,
+            terrain,
+            pheromones,
+            ants,
+            &mut m.wander_angle,
+            &mut m.waypoint_reached,
+            &mut rng,
         ),
     };
 
-    let (bx, by) = steering::boundary_avoidance(
-        x,
-        y,
-        cfg.world_width,
-        cfg.world_height,
-        cfg.boundary_margin,
-    );
-    steer_x += bx * 3.0;
-    steer_y += by * 3.0;
+    // steer away from the danger band around hazards
+    if !cfg.hazards.is_empty() {
+        if let Some(angle) = pheromones.sense_direction(
+            x,
+            y,
+            m.heading,
+            sensor_distance(cfg, ants, i),
+            cfg.ant_sensor_angle,
+            PheromoneType::Danger,
+        ) {
+            steer_x -= angle.cos() * cfg.hazard_avoid_weight;
+            steer_y -= angle.sin() * cfg.hazard_avoid_weight;
+        }
+    }
 
-    if let Some((wx, wy)) = steering::wall_avoidance(terrain, x, y, m.heading, cfg.wall_probe_distance) {
-        steer_x += wx * 4.0;
-        steer_y += wy * 4.0;
+    let (bx, by) =
+        steering::boundary_avoidance(x, y, cfg.world_width, cfg.world_height, cfg.boundary_margin);
+    steer_x += bx * cfg.boundary_steer_weight;
+    steer_y += by * cfg.boundary_steer_weight;
+
+    if let Some((wx, wy)) =
+        steering::wall_avoidance(terrain, x, y, m.heading, cfg.wall_probe_distance)
+    {
+        steer_x += wx * cfg.wall_steer_weight;
+        steer_y += wy * cfg.wall_steer_weight;
     }
 
     // ── integrate ──────────────────────────────────────────────────────
-    let desired = steer_y.atan2(steer_x);
-    let diff = steering::normalize_angle(desired - m.heading);
+    let desired = steer_heading(steer_x, steer_y, m.heading);
+    let diff = angle_diff(m.heading, desired);
     let max_turn = cfg.ant_turn_rate * dt;
-    let new_heading = steering::normalize_angle(m.heading + diff.clamp(-max_turn, max_turn));
+    let new_heading = normalize_angle(m.heading + diff.clamp(-max_turn, max_turn));
     m.heading = new_heading;
 
     let dx = new_heading.cos() * speed * dt;
@@ -434,7 +1610,7 @@ fn compute_ant_move(
         } else {
             nx = x;
             ny = y;
-            m.heading = steering::normalize_angle(new_heading + std::f32::consts::PI);
+            m.heading = normalize_angle(new_heading + std::f32::consts::PI);
         }
     }
 
@@ -451,6 +1627,7 @@ fn steer_foraging(
     x: f32,
     y: f32,
     heading: f32,
+    dt: f32,
     role: u8,
     cfg: &SimConfig,
     pheromones: &PheromoneField,
@@ -458,53 +1635,160 @@ fn steer_foraging(
     colonies: &[Colony],
     ants: &AntStorage,
     wander_angle: &mut f32,
+    discovered: &mut i32,
+    explore_target: &mut i32,
     rng: &mut SmallRng,
 ) -> (f32, f32) {
+    // nurses stay in the nest with the brood
+    if ants.task[i] == TASK_NURSE {
+        if let Some(c) = colonies.iter().find(|c| c.id == ants.colony_id[i]) {
+            let (sx, sy) = steering::seek(x, y, c.x, c.y);
+            let rng_val: f32 = rng.gen();
+            let (wx, wy) = steering::wander_direction(
+                heading,
+                wander_angle,
+                cfg.ant_wander_strength,
+                rng_val,
+                dt,
+            );
+            // inside the nest the pull fades so they mill about the brood
+            let dist = ((c.x - x).powi(2) + (c.y - y).powi(2)).sqrt();
+            let k = (dist / c.radius).clamp(0.2, 1.0);
+            return (sx * k + wx * (1.0 - k), sy * k + wy * (1.0 - k));
+        }
+    }
+
+    // raiders head straight for the rival nest
+    if let Some(c) = colonies.iter().find(|c| c.id as i32 == ants.raid_target[i]) {
+        let (sx, sy) = steering::seek(x, y, c.x, c.y);
+        let rng_val: f32 = rng.gen();
+        let (wx, wy) =
+            steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength, rng_val, dt);
+        return (sx * 0.8 + wx * 0.2, sy * 0.8 + wy * 0.2);
+    }
+
     // soldiers patrol near their colony instead of foraging
     if role == ROLE_SOLDIER {
-        return steer_patrol(x, y, heading, ants.colony_id[i], colonies, cfg, wander_angle, rng);
+        return steer_patrol(
+            x,
+            y,
+            heading,
+            dt,
+            ants.colony_id[i],
+            colonies,
+            cfg,
+            wander_angle,
+            rng,
+        );
     }
 
-    // direct vision: head toward the nearest visible food (scouts see further)
-    let det_mult = if role == ROLE_SCOUT { 1.8 } else { 1.0 };
+    // direct vision: head toward the nearest visible food (scouts see further).
+    // Food outside the cone ahead is only noticed much closer, and distances
+    // are compared in units of that reach so food ahead wins over food behind.
+    // A source in range is only noticed with a per-tick chance that falls with
+    // its difficulty; once noticed it becomes the ant's target and stays seen.
+    let (det_mult, discovery_mult) = if role == ROLE_SCOUT {
+        (cfg.scout_detection_boost, cfg.scout_discovery_boost)
+    } else {
+        (1.0, 1.0)
+    };
     let det_r_sq = (cfg.ant_detection_radius * det_mult).powi(2);
+    let target = ants.target_food[i];
     let mut best_dist_sq = det_r_sq;
-    let mut best_food: Option<(f32, f32)> = None;
+    let mut best_food: Option<&FoodSource> = None;
     for fs in food_sources {
-        if fs.amount < 1.0 {
-            continue;
-        }
         let dx = fs.x - x;
         let dy = fs.y - y;
-        let d2 = dx * dx + dy * dy;
-        if d2 < best_dist_sq {
+        let mut d2 = dx * dx + dy * dy;
+        if d2 >= det_r_sq {
+            continue;
+        }
+        let off_axis = angle_diff(heading, dy.atan2(dx)).abs();
+        if off_axis > cfg.ant_vision_half_angle {
+            d2 /= cfg.ant_rear_vision.max(1e-3).powi(2);
+        }
+        if d2 >= best_dist_sq {
+            continue;
+        }
+        let chance = cfg.food_discovery_chance * discovery_mult / fs.discovery_difficulty.max(0.1);
+        if fs.id as i32 == target || rng.gen::<f32>() < chance {
             best_dist_sq = d2;
-            best_food = Some((fs.x, fs.y));
+            best_food = Some(fs);
         }
     }
-    if let Some((fx, fy)) = best_food {
-        return steering::seek(x, y, fx, fy);
+    if let Some(fs) = best_food {
+        if fs.id as i32 != target {
+            *discovered = fs.id as i32;
+        }
+        return steering::seek(x, y, fs.x, fs.y);
+    }
+
+    // recruited workers head for food their colony's scouts reported
+    if let Some(fs) = food_sources.iter().find(|f| f.id as i32 == target) {
+        let (sx, sy) = steering::seek(x, y, fs.x, fs.y);
+        let rng_val: f32 = rng.gen();
+        let (wx, wy) = steering::wander_direction(
+            heading,
+            wander_angle,
+            cfg.ant_wander_strength * 0.4,
+            rng_val,
+            dt,
+        );
+        let k = cfg.trail_follow_weight;
+        return (sx * k + wx * (1.0 - k), sy * k + wy * (1.0 - k));
     }
 
     // follow the food pheromone gradient (scouts use a wider sensor spread)
     let sensor_angle = if role == ROLE_SCOUT {
-        cfg.ant_sensor_angle * 1.4
+        cfg.ant_sensor_angle * cfg.scout_sensor_spread
     } else {
         cfg.ant_sensor_angle
     };
-    if let Some(angle) = pheromones.sense_direction(
+    if let Some(angle) = pheromones.sense_food_trail(
         x,
         y,
         heading,
-        cfg.ant_sensor_distance,
+        sensor_distance(cfg, ants, i),
         sensor_angle,
-        PheromoneType::Food,
+        cfg.pheromone_direction_bias,
+        ants.colony_id[i],
+        cfg.rival_trail_weight,
     ) {
         let (px, py) = (angle.cos(), angle.sin());
         let rng_val: f32 = rng.gen();
-        let (wx, wy) =
-            steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength * 0.4, rng_val);
-        return (px * 0.7 + wx * 0.3, py * 0.7 + wy * 0.3);
+        let (wx, wy) = steering::wander_direction(
+            heading,
+            wander_angle,
+            cfg.ant_wander_strength * 0.4,
+            rng_val,
+            dt,
+        );
+        let k = cfg.trail_follow_weight;
+        return (px * k + wx * (1.0 - k), py * k + wy * (1.0 - k));
+    }
+
+    // scouts head for the nearest stretch their colony hasn't explored yet
+    if role == ROLE_SCOUT && cfg.scout_explore_cell > 0.0 {
+        if let Some(colony) = colonies.iter().find(|c| c.id == ants.colony_id[i]) {
+            let grid = &colony.explored;
+            if *explore_target < 0 || grid.is_visited(*explore_target as usize) {
+                *explore_target = grid.nearest_unvisited(x, y).map_or(-1, |c| c as i32);
+            }
+            if *explore_target >= 0 {
+                let (tx, ty) = grid.center(*explore_target as usize);
+                let (sx, sy) = steering::seek(x, y, tx, ty);
+                let rng_val: f32 = rng.gen();
+                let (wx, wy) = steering::wander_direction(
+                    heading,
+                    wander_angle,
+                    cfg.ant_wander_strength * cfg.scout_wander_boost,
+                    rng_val,
+                    dt,
+                );
+                let k = cfg.trail_follow_weight;
+                return (sx * k + wx * (1.0 - k), sy * k + wy * (1.0 - k));
+            }
+        }
     }
 
     // pure wander (scouts explore more aggressively)
@@ -514,7 +1798,23 @@ fn steer_foraging(
         cfg.ant_wander_strength
     };
     let rng_val: f32 = rng.gen();
-    steering::wander_direction(heading, wander_angle, wander_str, rng_val)
+    let (wx, wy) = steering::wander_direction(heading, wander_angle, wander_str, rng_val, dt);
+
+    // workers with nothing to go on drift back toward the queen's scent
+    if role != ROLE_SCOUT && cfg.queen_pheromone_range > 0.0 {
+        if let Some(angle) = pheromones.sense_direction(
+            x,
+            y,
+            heading,
+            sensor_distance(cfg, ants, i),
+            cfg.ant_sensor_angle,
+            PheromoneType::Queen,
+        ) {
+            let k = cfg.queen_pull_weight;
+            return (wx + angle.cos() * k, wy + angle.sin() * k);
+        }
+    }
+    (wx, wy)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -522,6 +1822,7 @@ fn steer_patrol(
     x: f32,
     y: f32,
     heading: f32,
+    dt: f32,
     colony_id: u32,
     colonies: &[Colony],
     cfg: &SimConfig,
@@ -531,10 +1832,10 @@ fn steer_patrol(
     let colony = colonies.iter().find(|c| c.id == colony_id);
     let Some(colony) = colony else {
         let r: f32 = rng.gen();
-        return steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength, r);
+        return steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength, r, dt);
     };
 
-    let patrol_r = cfg.soldier_patrol_radius;
+    let patrol_r = colony.patrol_radius(cfg.soldier_patrol_radius);
     let dx = x - colony.x;
     let dy = y - colony.y;
     let dist = (dx * dx + dy * dy).sqrt();
@@ -544,7 +1845,7 @@ fn steer_patrol(
             (dx / dist, dy / dist)
         } else {
             let r: f32 = rng.gen();
-            steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength, r)
+            steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength, r, dt)
         }
     } else if dist > patrol_r * 1.4 {
         steering::seek(x, y, colony.x, colony.y)
@@ -553,8 +1854,13 @@ fn steer_patrol(
         let tangent_x = -dy / dist;
         let tangent_y = dx / dist;
         let rng_val: f32 = rng.gen();
-        let (wx, wy) =
-            steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength * 0.5, rng_val);
+        let (wx, wy) = steering::wander_direction(
+            heading,
+            wander_angle,
+            cfg.ant_wander_strength * 0.5,
+            rng_val,
+            dt,
+        );
         (tangent_x * 0.7 + wx * 0.3, tangent_y * 0.7 + wy * 0.3)
     }
 }
@@ -565,15 +1871,30 @@ fn steer_returning(
     x: f32,
     y: f32,
     heading: f32,
+    dt: f32,
     cfg: &SimConfig,
+    terrain: &Terrain,
     pheromones: &PheromoneField,
     ants: &AntStorage,
     wander_angle: &mut f32,
+    waypoint_reached: &mut bool,
     rng: &mut SmallRng,
 ) -> (f32, f32) {
-    // path integration: direction toward the colony
     let hx = -ants.home_vec_x[i];
     let hy = -ants.home_vec_y[i];
+
+    // walled off from home: retrace the recorded outbound path
+    if let Some(&(wx, wy)) = ants.path[i].last() {
+        if !terrain.segment_clear(x, y, x + hx, y + hy) {
+            let reach = cfg.ant_waypoint_spacing * 0.5;
+            if dist_sq((wx, wy), (x, y)) < reach * reach {
+                *waypoint_reached = true;
+            }
+            return steering::seek(x, y, wx, wy);
+        }
+    }
+
+    // path integration: direction toward the colony
     let hmag = (hx * hx + hy * hy).sqrt();
     let (path_dx, path_dy) = if hmag > 0.001 {
         (hx / hmag, hy / hmag)
@@ -581,21 +1902,1531 @@ fn steer_returning(
         (0.0, 0.0)
     };
 
-    // blend with the home pheromone gradient
-    if let Some(angle) = pheromones.sense_direction(
-        x,
-        y,
-        heading,
-        cfg.ant_sensor_distance,
-        cfg.ant_sensor_angle,
-        PheromoneType::Home,
-    ) {
+    // blend with the home pheromone gradient, where it leads to our own nest
+    let own_gradient = pheromones.home_owner_at(x, y) == ants.colony_id[i];
+    let (dx, dy) = if let Some(angle) = own_gradient
+        .then(|| {
+            pheromones.sense_direction(
+                x,
+                y,
+                heading,
+                sensor_distance(cfg, ants, i),
+                cfg.ant_sensor_angle,
+                PheromoneType::Home,
+            )
+        })
+        .flatten()
+    {
         let (px, py) = (angle.cos(), angle.sin());
-        (path_dx * 0.4 + px * 0.6, path_dy * 0.4 + py * 0.6)
+        let k = cfg.home_gradient_weight;
+        (path_dx * (1.0 - k) + px * k, path_dy * (1.0 - k) + py * k)
     } else {
         let rng_val: f32 = rng.gen();
-        let (wx, wy) =
-            steering::wander_direction(heading, wander_angle, cfg.ant_wander_strength * 0.3, rng_val);
+        let (wx, wy) = steering::wander_direction(
+            heading,
+            wander_angle,
+            cfg.ant_wander_strength * 0.3,
+            rng_val,
+            dt,
+        );
         (path_dx * 0.7 + wx * 0.3, path_dy * 0.7 + wy * 0.3)
+    };
+
+    // trail laid by earlier carriers runs homeward too
+    let k = cfg.home_trail_weight;
+    if k > 0.0 {
+        if let Some(angle) = pheromones.sense_home_trail(
+            x,
+            y,
+            heading,
+            sensor_distance(cfg, ants, i),
+            cfg.ant_sensor_angle,
+            cfg.pheromone_direction_bias,
+            ants.colony_id[i],
+            cfg.rival_trail_weight,
+        ) {
+            return (
+                dx * (1.0 - k) + angle.cos() * k,
+                dy * (1.0 - k) + angle.sin() * k,
+            );
+        }
+    }
+    (dx, dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimOverrides;
+    use hazard::{Hazard, HazardKind};
+
+    fn food_at(id: u32, x: f32, y: f32) -> FoodSource {
+        FoodSource {
+            id,
+            x,
+            y,
+            amount: 100.0,
+            max_amount: 100.0,
+            discovery_difficulty: 0.1,
+            nutrition: 1.0,
+            kind: None,
+        }
+    }
+
+    #[test]
+    fn detection_radius_override_is_respected() {
+        let base: SimConfig = toml::from_str("food_discovery_chance = 1.0").unwrap();
+        let wide: SimConfig =
+            toml::from_str("food_discovery_chance = 1.0\nant_detection_radius = 150.0").unwrap();
+        assert_eq!(wide.ant_detection_radius, 150.0);
+
+        let pheromones = PheromoneField::new(1000.0, 1000.0, base.pheromone_cell_size);
+        let food = [food_at(3, 600.0, 500.0)];
+        let mut ants = AntStorage::new();
+        ants.add(500.0, 500.0, 0, ROLE_WORKER, 1.0, 0.0);
+
+        // food 100 units dead ahead: beyond the default reach, inside the override
+        let sees = |cfg: &SimConfig| {
+            let mut rng = SmallRng::seed_from_u64(1);
+            let (mut wander, mut discovered, mut explore) = (0.0, -1, -1);
+            steer_foraging(
+                0,
+                500.0,
+                500.0,
+                0.0,
+                1.0 / 60.0,
+                ROLE_WORKER,
+                cfg,
+                &pheromones,
+                &food,
+                &[],
+                &ants,
+                &mut wander,
+                &mut discovered,
+                &mut explore,
+                &mut rng,
+            );
+            discovered == 3
+        };
+        assert!(base.ant_detection_radius < 100.0);
+        assert!(!sees(&base));
+        assert!(sees(&wide));
+    }
+
+    #[test]
+    fn scouts_spot_food_beyond_a_workers_reach() {
+        let cfg = SimConfig {
+            food_discovery_chance: 1.0,
+            scout_detection_boost: 1.8,
+            ..SimConfig::small()
+        };
+        let pheromones = PheromoneField::new(1000.0, 1000.0, cfg.pheromone_cell_size);
+        let mut ants = AntStorage::new();
+        ants.add(500.0, 500.0, 0, ROLE_WORKER, 1.0, 0.0);
+        ants.add(500.0, 500.0, 0, ROLE_SCOUT, 1.0, 0.0);
+        let food = [food_at(4, 500.0 + cfg.ant_detection_radius * 1.4, 500.0)];
+
+        let sees = |i: usize| {
+            let mut rng = SmallRng::seed_from_u64(1);
+            let (mut wander, mut discovered, mut explore) = (0.0, -1, -1);
+            steer_foraging(
+                i,
+                500.0,
+                500.0,
+                0.0,
+                1.0 / 60.0,
+                ants.ant_type[i],
+                &cfg,
+                &pheromones,
+                &food,
+                &[],
+                &ants,
+                &mut wander,
+                &mut discovered,
+                &mut explore,
+                &mut rng,
+            );
+            discovered == 4
+        };
+        assert!(!sees(0));
+        assert!(sees(1));
+    }
+
+    #[test]
+    fn food_ahead_wins_over_equally_distant_food_behind() {
+        let cfg = SimConfig {
+            food_discovery_chance: 1.0,
+            ..SimConfig::small()
+        };
+        let pheromones = PheromoneField::new(1000.0, 1000.0, cfg.pheromone_cell_size);
+        let mut ants = AntStorage::new();
+        ants.add(500.0, 500.0, 0, ROLE_WORKER, 1.0, 0.0);
+
+        // both close enough to be seen even from behind
+        let d = cfg.ant_detection_radius * cfg.ant_rear_vision * 0.5;
+        let ahead = food_at(1, 500.0 + d, 500.0);
+        let behind = food_at(2, 500.0 - d, 500.0);
+        let target = |food: &[FoodSource]| {
+            let mut rng = SmallRng::seed_from_u64(1);
+            let (mut wander, mut discovered, mut explore) = (0.0, -1, -1);
+            let steer = steer_foraging(
+                0,
+                500.0,
+                500.0,
+                0.0,
+                1.0 / 60.0,
+                ROLE_WORKER,
+                &cfg,
+                &pheromones,
+                food,
+                &[],
+                &ants,
+                &mut wander,
+                &mut discovered,
+                &mut explore,
+                &mut rng,
+            );
+            (discovered, steer.0 > 0.0)
+        };
+        assert_eq!(target(&[behind.clone(), ahead.clone()]), (1, true));
+        assert_eq!(target(&[ahead, behind.clone()]), (1, true));
+        // on its own the food behind is still noticed
+        assert_eq!(target(&[behind]), (2, false));
+    }
+
+    #[test]
+    fn hard_to_find_food_is_discovered_later() {
+        let cfg = SimConfig {
+            food_discovery_chance: 0.05,
+            ..SimConfig::small()
+        };
+        let pheromones = PheromoneField::new(1000.0, 1000.0, cfg.pheromone_cell_size);
+        let mut ants = AntStorage::new();
+        ants.add(500.0, 500.0, 0, ROLE_WORKER, 1.0, 0.0);
+
+        // mean number of looks before a source in plain sight is noticed
+        let mean_looks = |difficulty: f32| {
+            let food = [FoodSource {
+                discovery_difficulty: difficulty,
+                ..food_at(1, 500.0 + cfg.ant_detection_radius * 0.5, 500.0)
+            }];
+            let trials = 400;
+            let mut looks = 0;
+            for seed in 0..trials {
+                let mut rng = SmallRng::seed_from_u64(seed);
+                let (mut wander, mut discovered, mut explore) = (0.0, -1, -1);
+                while discovered < 0 {
+                    looks += 1;
+                    steer_foraging(
+                        0,
+                        500.0,
+                        500.0,
+                        0.0,
+                        1.0 / 60.0,
+                        ROLE_WORKER,
+                        &cfg,
+                        &pheromones,
+                        &food,
+                        &[],
+                        &ants,
+                        &mut wander,
+                        &mut discovered,
+                        &mut explore,
+                        &mut rng,
+                    );
+                }
+            }
+            looks as f32 / trials as f32
+        };
+        let easy = mean_looks(0.1);
+        let hard = mean_looks(1.0);
+        assert!(easy < 3.0, "{easy}");
+        assert!(hard > easy * 5.0, "easy {easy}, hard {hard}");
+    }
+
+    #[test]
+    fn a_scout_with_no_trail_heads_for_unexplored_ground() {
+        let cfg = SimConfig {
+            scout_explore_cell: 100.0,
+            ..SimConfig::small()
+        };
+        let mut colony = SimulationState::new(cfg.clone()).colonies[0].clone();
+        // everything explored but the cell south of the scout
+        let (gap_x, gap_y) = (550.0, 850.0);
+        for gy in 0..9 {
+            for gx in 0..12 {
+                let (x, y) = (gx as f32 * 100.0 + 50.0, gy as f32 * 100.0 + 50.0);
+                if (x, y) != (gap_x, gap_y) {
+                    colony.explored.visit(x, y);
+                }
+            }
+        }
+        let gap = colony.explored.nearest_unvisited(500.0, 500.0).unwrap();
+        assert_eq!(colony.explored.center(gap), (gap_x, gap_y));
+
+        let pheromones = PheromoneField::new(1200.0, 900.0, cfg.pheromone_cell_size);
+        let mut ants = AntStorage::new();
+        ants.add(500.0, 500.0, colony.id, ROLE_SCOUT, 1.0, 0.0);
+        for seed in 0..20 {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let (mut wander, mut discovered, mut explore) = (0.0, -1, -1);
+            // heading east, with the gap to the south
+            let (sx, sy) = steer_foraging(
+                0,
+                500.0,
+                500.0,
+                0.0,
+                1.0 / 60.0,
+                ROLE_SCOUT,
+                &cfg,
+                &pheromones,
+                &[],
+                std::slice::from_ref(&colony),
+                &ants,
+                &mut wander,
+                &mut discovered,
+                &mut explore,
+                &mut rng,
+            );
+            assert_eq!(explore, gap as i32);
+            let towards = sy.atan2(sx);
+            let to_gap = (gap_y - 500.0).atan2(gap_x - 500.0);
+            assert!(
+                angle_diff(towards, to_gap).abs() < angle_diff(0.0, to_gap).abs(),
+                "seed {seed}: steering {towards}"
+            );
+        }
+    }
+
+    #[test]
+    fn sensitive_ants_pick_up_a_trail_from_farther_away() {
+        let cfg = SimConfig {
+            ant_sensitivity_spread: 0.0,
+            ..SimConfig::small()
+        };
+        let mut pheromones = PheromoneField::new(1000.0, 1000.0, cfg.pheromone_cell_size);
+        let mut ants = AntStorage::new();
+        ants.add(500.0, 500.0, 0, ROLE_WORKER, 1.0, 0.0);
+        ants.add(500.0, 500.0, 0, ROLE_WORKER, 1.0, 0.0);
+        ants.sensitivity_gain[1] = 1.0;
+
+        // a trail twice the normal sensor distance ahead
+        let ahead = cfg.ant_sensor_distance * 2.0;
+        pheromones.deposit_food(500.0 + ahead, 500.0, std::f32::consts::PI, 1.0, 0);
+        let senses = |i: usize| {
+            pheromones
+                .sense_food_trail(
+                    500.0,
+                    500.0,
+                    0.0,
+                    sensor_distance(&cfg, &ants, i),
+                    cfg.ant_sensor_angle,
+                    0.0,
+                    0,
+                    1.0,
+                )
+                .is_some()
+        };
+        assert!(!senses(0));
+        assert!(senses(1));
+    }
+
+    #[test]
+    fn seekers_walk_a_trail_toward_food_and_carriers_toward_home() {
+        // x moved by a seeker and a carrier that start facing north on a
+        // broad trail laid by ants walking `laid_heading`
+        let drift = |laid_heading: f32| {
+            let mut sim = empty_sim(SimConfig {
+                levy_probability: 0.0,
+                pheromone_evaporation: 1.0,
+                pheromone_diffusion_rate: 0.0,
+                home_gradient_weight: 0.0,
+                ..SimConfig::small()
+            });
+            for y in (500..800).step_by(8) {
+                for x in (100..1100).step_by(8) {
+                    sim.pheromones
+                        .deposit_food(x as f32, y as f32, laid_heading, 1.0, 0);
+                }
+            }
+            let north = -std::f32::consts::FRAC_PI_2;
+            sim.ants.add(400.0, 750.0, 0, ROLE_WORKER, 80.0, north);
+            sim.ants.add(800.0, 750.0, 0, ROLE_WORKER, 80.0, north);
+            // the carrier's home lies due north by dead reckoning
+            sim.ants.state[1] = AntState::Returning;
+            sim.ants.home_vec_y[1] = 1000.0;
+            for _ in 0..30 {
+                sim.tick();
+            }
+            (sim.ants.pos_x[0] - 400.0, sim.ants.pos_x[1] - 800.0)
+        };
+
+        // laid walking west: the food lies east, home west
+        let (seeker, carrier) = drift(std::f32::consts::PI);
+        assert!(seeker > 10.0, "seeker drifted {seeker}");
+        assert!(carrier < -5.0, "carrier drifted {carrier}");
+
+        // laid walking east: the other way round
+        let (seeker, carrier) = drift(0.0);
+        assert!(seeker < -10.0, "seeker drifted {seeker}");
+        assert!(carrier > 5.0, "carrier drifted {carrier}");
+    }
+
+    /// A simulation with no ants and no food, for a test to fill by hand.
+    fn empty_sim(cfg: SimConfig) -> SimulationState {
+        let mut sim = SimulationState::new(cfg);
+        sim.ants = AntStorage::new();
+        sim.food_sources.clear();
+        sim
+    }
+
+    /// A sim with one motionless worker standing on a food source.
+    fn forager_on_food(cfg: SimConfig) -> SimulationState {
+        let mut sim = empty_sim(cfg);
+        sim.ants
+            .add(300.0, 300.0, sim.colonies[0].id, ROLE_WORKER, 0.0, 0.0);
+        sim.food_sources.push(food_at(0, 300.0, 300.0));
+        sim
+    }
+
+    #[test]
+    fn a_partial_load_is_topped_up_before_heading_home() {
+        let cfg = SimConfig {
+            ant_carry_capacity: 10.0,
+            ant_return_fill: 0.5,
+            ..SimConfig::small()
+        };
+        let mut light = forager_on_food(SimConfig {
+            food_pickup_amount: 1.0,
+            ..cfg.clone()
+        });
+        light.tick();
+        assert_eq!(light.ants.cargo[0], 1.0);
+        assert_eq!(light.ants.state[0], AntState::Foraging);
+
+        let mut heavy = forager_on_food(SimConfig {
+            food_pickup_amount: 9.0,
+            ..cfg.clone()
+        });
+        heavy.tick();
+        assert_eq!(heavy.ants.cargo[0], 9.0);
+        assert_eq!(heavy.ants.state[0], AntState::Returning);
+
+        // a pickup bigger than the ant can carry is capped at capacity
+        let mut greedy = forager_on_food(SimConfig {
+            food_pickup_amount: 25.0,
+            ..cfg
+        });
+        greedy.tick();
+        assert_eq!(greedy.ants.cargo[0], 10.0);
+        assert_eq!(greedy.food_sources[0].amount, 90.0);
+    }
+
+    #[test]
+    fn a_scouts_report_sends_a_distant_worker_to_the_food() {
+        let mut sim = forager_on_food(SimConfig {
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        sim.ants.ant_type[0] = ROLE_SCOUT;
+        // a worker unloading in the nest, well out of sight of the food
+        let c = sim.colonies[0].clone();
+        sim.ants.add(c.x, c.y, c.id, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.state[1] = AntState::Returning;
+        sim.ants.cargo[1] = 1.0;
+        let food = (sim.food_sources[0].x, sim.food_sources[0].y);
+        assert!(dist_sq((c.x, c.y), food) > (3.0 * sim.config.ant_detection_radius).powi(2));
+
+        sim.tick();
+        assert_eq!(sim.colonies[0].known_food, [0]);
+        assert_eq!(sim.ants.state[1], AntState::Foraging);
+        assert_eq!(sim.ants.target_food[1], 0);
+
+        sim.ants.speed[1] = sim.config.ant_max_speed;
+        let start = dist_sq((sim.ants.pos_x[1], sim.ants.pos_y[1]), food).sqrt();
+        for _ in 0..120 {
+            sim.tick();
+        }
+        let end = dist_sq((sim.ants.pos_x[1], sim.ants.pos_y[1]), food).sqrt();
+        assert!(end < start - 100.0, "{start} -> {end}");
+    }
+
+    #[test]
+    fn a_tired_ant_lays_a_weaker_trail() {
+        let trail_from = |energy: f32| {
+            let mut sim = empty_sim(SimConfig::small());
+            sim.ants
+                .add(300.0, 300.0, sim.colonies[0].id, ROLE_WORKER, 0.0, 0.0);
+            sim.ants.state[0] = AntState::Returning;
+            sim.ants.cargo[0] = 1.0;
+            sim.ants.cargo_quality[0] = 1.0;
+            sim.ants.energy[0] = energy;
+            sim.tick();
+            sim.pheromones.food.iter().sum::<f32>()
+        };
+        let fresh = trail_from(100.0);
+        let tired = trail_from(5.0);
+        assert!(tired > 0.0);
+        assert!(tired < fresh * 0.5, "fresh {fresh}, tired {tired}");
+    }
+
+    #[test]
+    fn a_loaded_ant_covers_less_ground_in_a_second() {
+        let cfg = SimConfig {
+            ant_carry_capacity: 10.0,
+            ant_laden_speed: 0.5,
+            ..SimConfig::small()
+        };
+        // ground covered by an ant walking home with `cargo` for one second
+        let covered = |cargo: f32| {
+            let mut sim = empty_sim(cfg.clone());
+            let c = sim.colonies[0].clone();
+            sim.ants
+                .add(200.0, c.y, c.id, ROLE_WORKER, cfg.ant_max_speed, 0.0);
+            sim.ants.state[0] = AntState::Returning;
+            sim.ants.cargo[0] = cargo;
+            for _ in 0..cfg.tick_rate as usize {
+                sim.tick();
+            }
+            dist_sq((200.0, c.y), (sim.ants.pos_x[0], sim.ants.pos_y[0])).sqrt()
+        };
+        let light = covered(0.5);
+        let heavy = covered(10.0);
+        assert!(light > 0.0);
+        assert!(heavy < light * 0.7, "light {light}, heavy {heavy}");
+    }
+
+    #[test]
+    fn a_carrier_walled_off_from_home_retraces_its_path() {
+        let mut sim = empty_sim(SimConfig {
+            ant_path_waypoints: 16,
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        let c = sim.colonies[0].clone();
+        assert_eq!((c.x, c.y), (600.0, 450.0));
+        // a wall west of the nest, from y = 304 down to the bottom edge
+        let t = &mut sim.terrain;
+        for gy in (304 / 8)..t.grid_h {
+            for gx in (488 / 8)..(512 / 8) {
+                let idx = t.idx(gx, gy);
+                t.solid[idx] = 1;
+            }
+        }
+        sim.pheromones.build_blocked_mask(&sim.terrain);
+
+        // the way out went north, round the top of the wall and back south
+        let outbound = vec![
+            (600.0, 450.0),
+            (600.0, 350.0),
+            (600.0, 250.0),
+            (550.0, 250.0),
+            (500.0, 250.0),
+            (450.0, 250.0),
+            (400.0, 250.0),
+            (400.0, 330.0),
+            (400.0, 410.0),
+        ];
+        let speed = sim.config.ant_max_speed;
+        sim.ants.add(
+            400.0,
+            440.0,
+            c.id,
+            ROLE_WORKER,
+            speed,
+            -std::f32::consts::FRAC_PI_2,
+        );
+        sim.ants.path[0] = outbound;
+        sim.ants.state[0] = AntState::Returning;
+        sim.ants.cargo[0] = 1.0;
+        sim.ants.cargo_quality[0] = 1.0;
+        (sim.ants.home_vec_x[0], sim.ants.home_vec_y[0]) = (400.0 - c.x, 440.0 - c.y);
+
+        // thinned, the straight runs collapse but the corners round the
+        // wall stay, since cutting them would cross it
+        assert_eq!(
+            sim.simplified_path(0),
+            [
+                (600.0, 450.0),
+                (600.0, 250.0),
+                (400.0, 250.0),
+                (400.0, 410.0)
+            ]
+        );
+
+        let mut lowest = f32::MAX;
+        for _ in 0..1200 {
+            sim.tick();
+            if sim.ants.state[0] == AntState::Foraging {
+                break;
+            }
+            lowest = lowest.min(sim.ants.pos_y[0]);
+        }
+        assert_eq!(sim.ants.state[0], AntState::Foraging, "never got home");
+        assert_eq!(sim.total_food_collected, 1.0);
+        // it went round the top of the wall, not through it
+        assert!(lowest < 304.0, "{lowest}");
+    }
+
+    #[test]
+    fn an_emptied_source_is_forgotten_everywhere() {
+        let mut sim = forager_on_food(SimConfig {
+            ant_carry_capacity: 10.0,
+            ant_return_fill: 1.0,
+            food_pickup_amount: 1.0,
+            ..SimConfig::small()
+        });
+        sim.food_sources[0].amount = 3.0;
+        // a second forager across the map was sent to the same source
+        let colony = sim.colonies[0].id;
+        sim.ants.add(900.0, 700.0, colony, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.target_food[1] = 0;
+        sim.colonies[0].known_food = vec![0];
+
+        for _ in 0..3 {
+            assert_eq!(sim.food_sources.len(), 1);
+            sim.tick();
+        }
+        assert_eq!(sim.ants.cargo[0], 3.0);
+        assert!(sim.food_sources.is_empty());
+        assert!(sim.colonies[0].known_food.is_empty());
+        assert!(sim.ants.target_food.iter().all(|&t| t != 0));
+    }
+
+    #[test]
+    fn a_big_load_is_unloaded_over_several_ticks() {
+        let mut sim = empty_sim(SimConfig {
+            ant_carry_capacity: 20.0,
+            ant_unload_rate: 5.0,
+            colony_initial_food: 0.0,
+            colony_upkeep_per_ant: 0.0,
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        let c = sim.colonies[0].clone();
+        sim.ants.add(c.x, c.y, c.id, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.state[0] = AntState::Returning;
+        sim.ants.cargo[0] = 20.0;
+        sim.ants.cargo_nutrition[0] = 1.0;
+
+        for k in 1..=4 {
+            assert_eq!(sim.ants.state[0], AntState::Returning);
+            sim.tick();
+            assert_eq!(sim.ants.cargo[0], 20.0 - 5.0 * k as f32);
+            assert_eq!(sim.colonies[0].food_stored, 5.0 * k as f32);
+        }
+        assert_eq!(sim.ants.state[0], AntState::Foraging);
+        assert_eq!(sim.total_food_collected, 20.0);
+    }
+
+    #[test]
+    fn an_emptied_source_grows_back_after_the_respawn_delay() {
+        let mut sim = forager_on_food(SimConfig {
+            food_respawn_ticks: 50,
+            food_respawn_fraction: 0.6,
+            ..SimConfig::small()
+        });
+        sim.food_sources[0].amount = 1.0;
+        sim.tick();
+        assert!(sim.food_sources.is_empty());
+        let emptied = sim.tick_count;
+        // keep the forager from picking it clean again
+        sim.ants.remove(0);
+
+        while sim.food_sources.is_empty() {
+            assert!(sim.tick_count < emptied + 100, "never grew back");
+            sim.tick();
+        }
+        assert_eq!(sim.tick_count, emptied + 50);
+        let back = &sim.food_sources[0];
+        assert_eq!((back.id, back.x, back.y), (0, 300.0, 300.0));
+        assert!((back.amount - 60.0).abs() < 1e-3);
+        assert_eq!(back.amount, back.max_amount);
+    }
+
+    #[test]
+    fn richer_food_of_the_same_weight_grows_the_colony_more() {
+        let eggs_from = |nutrition: f32| {
+            let mut sim = empty_sim(SimConfig {
+                colony_initial_food: 0.0,
+                colony_upkeep_per_ant: 0.0,
+                colony_spawn_interval: 1,
+                colony_spawn_batch: 100,
+                colony_spawn_cost: 1.0,
+                ant_carry_capacity: 20.0,
+                ..SimConfig::small()
+            });
+            let c = sim.colonies[0].clone();
+            sim.ants.add(c.x, c.y, c.id, ROLE_WORKER, 0.0, 0.0);
+            sim.ants.state[0] = AntState::Returning;
+            sim.ants.cargo[0] = 20.0;
+            sim.ants.cargo_nutrition[0] = nutrition;
+            sim.tick();
+            assert_eq!(sim.ants.cargo[0], 0.0);
+            sim.colonies[0].brood.len()
+        };
+        assert_eq!(eggs_from(2.0), 40);
+        assert_eq!(eggs_from(0.5), 10);
+    }
+
+    #[test]
+    fn colonies_start_with_the_configured_food() {
+        let sim = SimulationState::new(SimConfig {
+            colony_count: 3,
+            colony_initial_food: 777.0,
+            ..SimConfig::small()
+        });
+        assert!(sim.colonies.iter().all(|c| c.food_stored == 777.0));
+
+        // and from a simulation row's overrides
+        let overrides =
+            SimOverrides::from_json(&serde_json::json!({ "initial_food": 50 })).unwrap();
+        let cfg = SimConfig::from_row(&SimConfig::small(), 2, 1200, 900, overrides);
+        let sim = SimulationState::new(cfg);
+        assert_eq!(sim.colonies[0].food_stored, 50.0);
+    }
+
+    #[test]
+    fn each_colony_starts_with_its_own_population() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 3,
+            colony_starting_population: 30,
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        assert_eq!(sim.ants.count, 90);
+        let members = |sim: &SimulationState, id: u32| {
+            (0..sim.ants.count)
+                .filter(|&i| sim.ants.colony_id[i] == id)
+                .count()
+        };
+        for c in &sim.colonies {
+            assert_eq!(members(&sim, c.id), 30);
+            assert_eq!(c.population, 30);
+        }
+        sim.tick();
+        for c in &sim.colonies {
+            assert_eq!(members(&sim, c.id), 30);
+            assert_eq!(c.population, 30);
+        }
+    }
+
+    #[test]
+    fn ants_and_brood_never_exceed_max_ants() {
+        let mut sim = SimulationState::new(SimConfig {
+            max_ants: 80,
+            colony_spawn_interval: 1,
+            colony_spawn_batch: 50,
+            colony_spawn_cost: 0.1,
+            brood_egg_ticks: 5,
+            brood_larva_ticks: 5,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].food_stored = 1_000_000.0;
+        let mut peak = 0;
+        for _ in 0..600 {
+            sim.tick();
+            let brood: usize = sim.colonies.iter().map(|c| c.brood.len()).sum();
+            assert!(
+                sim.ants.count + brood <= 80,
+                "tick {}: {} ants + {} brood",
+                sim.tick_count,
+                sim.ants.count,
+                brood
+            );
+            peak = peak.max(sim.ants.count + brood);
+        }
+        assert_eq!(peak, 80);
+
+        // ants dropped in by hand leave room for the brood too
+        let mut sim = SimulationState::new(SimConfig {
+            max_ants: 80,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].brood = (0..15)
+            .map(|_| Brood {
+                stage: BroodStage::Egg,
+                role: ROLE_WORKER,
+                ticks: 0,
+            })
+            .collect();
+        assert_eq!(sim.ants.count, 60);
+        assert_eq!(sim.spawn_ants_at(600.0, 450.0, 10, None), 5);
+        assert_eq!(sim.spawn_ants_at(600.0, 450.0, 10, None), 0);
+    }
+
+    #[test]
+    fn wind_carries_a_trail_downwind() {
+        // trail-weighted centre of the food layer after `ticks` of wind
+        let centre_after = |wind_direction: f32, ticks: usize| {
+            let mut sim = empty_sim(SimConfig {
+                wind_speed: 20.0,
+                wind_direction,
+                ..SimConfig::small()
+            });
+            for dx in [-10.0, 0.0, 10.0] {
+                sim.pheromones.deposit_food(300.0 + dx, 300.0, 0.0, 1.0, 0);
+            }
+            for _ in 0..ticks {
+                sim.tick();
+            }
+            let p = &sim.pheromones;
+            let (mut sum, mut sx, mut sy) = (0.0, 0.0, 0.0);
+            for (idx, &v) in p.food.iter().enumerate() {
+                let (gx, gy) = (idx % p.grid_w, idx / p.grid_w);
+                sum += v;
+                sx += v * (gx as f32 + 0.5) * p.cell_size;
+                sy += v * (gy as f32 + 0.5) * p.cell_size;
+            }
+            (sx / sum, sy / sum)
+        };
+        let (x0, y0) = centre_after(0.0, 0);
+        let (ex, ey) = centre_after(0.0, 180);
+        assert!(ex > x0 + 20.0, "east wind: {x0} -> {ex}");
+        assert!((ey - y0).abs() < 5.0);
+        let (nx, ny) = centre_after(-std::f32::consts::FRAC_PI_2, 180);
+        assert!(ny < y0 - 20.0, "north wind: {y0} -> {ny}");
+        assert!((nx - x0).abs() < 5.0);
+    }
+
+    #[test]
+    fn queen_scent_keeps_idle_workers_near_the_nest() {
+        // mean distance of the workers from the nest after ten seconds with
+        // nothing to forage
+        let spread = |queen_pheromone_range: f32| {
+            let mut sim = SimulationState::new(SimConfig {
+                role_mix: [1.0, 0.0, 0.0],
+                colony_spawn_interval: 1_000_000,
+                queen_pheromone_range,
+                ..SimConfig::small()
+            });
+            sim.food_sources.clear();
+            for _ in 0..600 {
+                sim.tick();
+            }
+            let c = &sim.colonies[0];
+            let n = sim.ants.count;
+            (0..n)
+                .map(|i| dist_sq((sim.ants.pos_x[i], sim.ants.pos_y[i]), (c.x, c.y)).sqrt())
+                .sum::<f32>()
+                / n as f32
+        };
+        let with_queen = spread(250.0);
+        let without = spread(0.0);
+        assert!(
+            with_queen < without * 0.8,
+            "with queen {with_queen}, without {without}"
+        );
+    }
+
+    #[test]
+    fn an_aggressive_colony_raises_more_soldiers_from_the_same_budget() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 2,
+            colony_starting_population: 30,
+            colony_spawn_interval: 1,
+            colony_spawn_batch: 200,
+            colony_max_population: 10_000,
+            max_ants: 10_000,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].aggression = 1.0;
+        sim.colonies[1].aggression = 0.1;
+        for c in &mut sim.colonies {
+            c.food_stored = 1_000_000.0;
+        }
+        sim.lay_eggs();
+
+        let soldiers = |c: &Colony| c.brood.iter().filter(|b| b.role == ROLE_SOLDIER).count();
+        let (fierce, meek) = (&sim.colonies[0], &sim.colonies[1]);
+        assert_eq!(fierce.brood.len(), 200);
+        assert_eq!(meek.brood.len(), 200);
+        assert!(
+            soldiers(fierce) > soldiers(meek) * 3,
+            "{} vs {}",
+            soldiers(fierce),
+            soldiers(meek)
+        );
+    }
+
+    #[test]
+    fn eggs_are_laid_only_on_spawn_interval_ticks() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_spawn_interval: 7,
+            colony_spawn_batch: 2,
+            brood_egg_ticks: 1_000,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].food_stored = 100_000.0;
+        let mut laid_on = Vec::new();
+        for _ in 0..50 {
+            let before = sim.colonies[0].brood.len();
+            sim.tick();
+            if sim.colonies[0].brood.len() > before {
+                laid_on.push(sim.tick_count);
+            }
+        }
+        assert_eq!(laid_on, [7, 14, 21, 28, 35, 42, 49]);
+    }
+
+    #[test]
+    fn a_tired_ant_recovers_faster_resting_in_the_nest() {
+        let mut sim = empty_sim(SimConfig {
+            ant_nest_rest_recovery: 0.2,
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        let c = sim.colonies[0].clone();
+        sim.colonies[0].food_stored = 1_000.0;
+        sim.ants.add(c.x, c.y, c.id, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.add(200.0, 200.0, c.id, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.energy[0] = 10.0;
+        sim.ants.energy[1] = 10.0;
+        for _ in 0..30 {
+            sim.tick();
+        }
+        let (rested, fed) = (sim.ants.energy[0] - 10.0, sim.ants.energy[1] - 10.0);
+        assert!(fed > 0.0);
+        assert!(rested > fed * 3.0, "rested {rested}, fed {fed}");
+    }
+
+    #[test]
+    fn stored_food_spoils_over_time() {
+        let store_after = |colony_food_spoilage: f32| {
+            let mut sim = empty_sim(SimConfig {
+                colony_food_spoilage,
+                colony_spawn_interval: 1_000_000,
+                ..SimConfig::small()
+            });
+            sim.colonies[0].food_stored = 1_000.0;
+            let mut last = 1_000.0;
+            for _ in 0..200 {
+                sim.tick();
+                let now = sim.colonies[0].food_stored;
+                assert!(now <= last);
+                last = now;
+            }
+            last
+        };
+        assert_eq!(store_after(0.0), 1_000.0);
+        let spoiled = store_after(0.001);
+        let expected = 1_000.0 * 0.999f32.powi(200);
+        assert!((spoiled - expected).abs() < 1.0, "{spoiled} vs {expected}");
+    }
+
+    #[test]
+    fn a_carrier_walks_through_a_rival_nest_to_unload_at_its_own() {
+        let mut sim = empty_sim(SimConfig {
+            colony_count: 2,
+            colony_layout: ColonyLayout::Line,
+            colony_upkeep_per_ant: 0.0,
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        let (rival, home) = (sim.colonies[0].clone(), sim.colonies[1].clone());
+        let speed = sim.config.ant_max_speed;
+        sim.ants
+            .add(rival.x, rival.y, home.id, ROLE_WORKER, speed, 0.0);
+        sim.ants.state[0] = AntState::Returning;
+        sim.ants.cargo[0] = 5.0;
+        sim.ants.cargo_nutrition[0] = 1.0;
+        (sim.ants.home_vec_x[0], sim.ants.home_vec_y[0]) = (rival.x - home.x, rival.y - home.y);
+        let stores =
+            |sim: &SimulationState| [sim.colonies[0].food_stored, sim.colonies[1].food_stored];
+        let before = stores(&sim);
+
+        // standing in the rival nest, nothing is unloaded
+        sim.tick();
+        assert_eq!(sim.ants.cargo[0], 5.0);
+        assert_eq!(sim.ants.state[0], AntState::Returning);
+        assert_eq!(stores(&sim), before);
+
+        for _ in 0..1200 {
+            if sim.ants.state[0] == AntState::Foraging {
+                break;
+            }
+            sim.tick();
+        }
+        assert_eq!(sim.ants.state[0], AntState::Foraging, "never got home");
+        assert_eq!(sim.ants.cargo[0], 0.0);
+        let after = stores(&sim);
+        assert_eq!(after[0], before[0]);
+        assert_eq!(after[1], before[1] + 5.0);
+    }
+
+    #[test]
+    fn a_drained_colony_sends_more_workers_foraging() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 2,
+            colony_starting_population: 40,
+            role_mix: [1.0, 0.0, 0.0],
+            worker_nurse_share: 0.5,
+            brood_larvae_per_nurse: 1,
+            ..SimConfig::small()
+        });
+        let upkeep = sim.config.colony_upkeep_per_ant;
+        for (c, food) in sim.colonies.iter_mut().zip([0.0, 10_000.0]) {
+            c.food_stored = food;
+            c.brood = vec![
+                Brood {
+                    stage: BroodStage::Larva,
+                    role: ROLE_WORKER,
+                    ticks: 0,
+                };
+                40
+            ];
+            c.update_need(upkeep);
+        }
+        assert!(sim.colonies[0].needs_food);
+        assert!(!sim.colonies[1].needs_food);
+
+        sim.assign_tasks();
+        let foragers = |id: u32| {
+            (0..sim.ants.count)
+                .filter(|&i| sim.ants.colony_id[i] == id && sim.ants.task[i] == TASK_FORAGER)
+                .count()
+        };
+        let (drained, full) = (foragers(sim.colonies[0].id), foragers(sim.colonies[1].id));
+        assert_eq!(full, 20);
+        assert_eq!(drained, 30);
+    }
+
+    #[test]
+    fn wander_depends_on_the_ant_id_and_repeats_under_a_seed() {
+        // positions of a lone forager with `id` over two seconds
+        let walk = |id: u32, terrain_seed: u64| {
+            let mut sim = empty_sim(SimConfig {
+                terrain_seed,
+                colony_spawn_interval: 1_000_000,
+                ..SimConfig::small()
+            });
+            let speed = sim.config.ant_max_speed;
+            sim.ants
+                .add(300.0, 300.0, sim.colonies[0].id, ROLE_WORKER, speed, 0.0);
+            sim.ants.id[0] = id;
+            (0..120)
+                .map(|_| {
+                    sim.tick();
+                    (sim.ants.pos_x[0], sim.ants.pos_y[0])
+                })
+                .collect::<Vec<_>>()
+        };
+        let path = walk(3, 1);
+        assert_eq!(walk(3, 1), path);
+        assert_ne!(walk(4, 1), path);
+        assert_ne!(walk(3, 2), path);
+    }
+
+    #[test]
+    fn a_saturated_colony_founds_a_second_one() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_max_population: 60,
+            colony_founding_ticks: 20,
+            colony_founding_group: 15,
+            colony_founding_distance: 350.0,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].food_stored = 10_000.0;
+        for _ in 0..200 {
+            sim.tick();
+            if sim.colonies.len() > 1 {
+                break;
+            }
+        }
+        assert_eq!(sim.colonies.len(), 2);
+        let (parent, child) = (&sim.colonies[0], &sim.colonies[1]);
+        assert!(child.population > 0);
+        let members = (0..sim.ants.count)
+            .filter(|&i| sim.ants.colony_id[i] == child.id)
+            .count();
+        assert_eq!(members, child.population);
+        assert!(dist_sq((parent.x, parent.y), (child.x, child.y)) >= 350.0 * 350.0);
+    }
+
+    #[test]
+    fn raiders_carry_a_rivals_store_home_without_counting_it_as_collected() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 2,
+            colony_labor_interval: 1,
+            colony_spawn_interval: 1_000_000,
+            ant_carry_capacity: 50.0,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].aggression = 1.0;
+        sim.colonies[1].aggression = 0.0;
+        sim.colonies[1].food_stored = 1000.0;
+        let (home, rival) = (sim.colonies[0].clone(), sim.colonies[1].clone());
+        sim.launch_raids();
+        let i = (0..sim.ants.count)
+            .find(|&i| sim.ants.raid_target[i] == rival.id as i32)
+            .expect("no raid launched");
+        assert_eq!(sim.ants.colony_id[i], home.id);
+        sim.ants.speed[i] = 0.0;
+
+        // into the rival nest: a full load is taken from its store
+        (sim.ants.pos_x[i], sim.ants.pos_y[i]) = (rival.x, rival.y);
+        sim.tick();
+        let i = (0..sim.ants.count)
+            .find(|&k| sim.ants.cargo_looted[k])
+            .unwrap();
+        assert_eq!(sim.ants.cargo[i], 50.0);
+        assert!(sim.colonies[1].food_stored <= 950.0);
+
+        // back home: the loot is stored but not counted as collected
+        let stored = sim.colonies[0].food_stored;
+        (sim.ants.pos_x[i], sim.ants.pos_y[i]) = (home.x, home.y);
+        sim.tick();
+        assert_eq!(sim.ants.cargo[i], 0.0);
+        assert!(!sim.ants.cargo_looted[i]);
+        assert!(sim.colonies[0].food_stored > stored + 45.0);
+        assert_eq!(sim.total_food_collected, 0.0);
+        assert_eq!(sim.colonies[0].foraging.avg_trip_ticks, None);
+        assert_eq!(sim.ants.sensitivity_gain[i], 0.0);
+    }
+
+    #[test]
+    fn a_raider_with_no_carry_cap_takes_the_whole_store() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 2,
+            colony_spawn_interval: 1_000_000,
+            ant_carry_capacity: 0.0,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].aggression = 1.0;
+        sim.colonies[1].food_stored = 1000.0;
+        let rival = sim.colonies[1].clone();
+        sim.launch_raids();
+        let i = (0..sim.ants.count)
+            .find(|&i| sim.ants.raid_target[i] == rival.id as i32)
+            .expect("no raid launched");
+        sim.ants.speed[i] = 0.0;
+        (sim.ants.pos_x[i], sim.ants.pos_y[i]) = (rival.x, rival.y);
+        sim.tick();
+        let i = (0..sim.ants.count)
+            .find(|&k| sim.ants.cargo_looted[k])
+            .expect("nothing looted");
+        assert_eq!(sim.ants.cargo[i], 1000.0);
+        assert_eq!(sim.colonies[1].food_stored, 0.0);
+    }
+
+    #[test]
+    fn trip_time_is_recorded_after_a_forage_cycle() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        let (cx, cy) = (sim.colonies[0].x, sim.colonies[0].y);
+        for k in 0..8 {
+            let a = k as f32 * std::f32::consts::FRAC_PI_4;
+            sim.spawn_food(cx + a.cos() * 80.0, cy + a.sin() * 80.0, 500.0);
+        }
+        for _ in 0..FORAGING_WINDOW {
+            sim.tick();
+        }
+        let foraging = &sim.colonies[0].foraging;
+        let trip = foraging.avg_trip_ticks.expect("no trip completed");
+        assert!(trip > 0.0);
+        assert!(foraging.deposit_rate > 0.0);
+    }
+
+    #[test]
+    fn an_egg_matures_into_a_walking_ant() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_spawn_interval: 1_000_000,
+            colony_labor_interval: 5,
+            worker_nurse_share: 0.5,
+            brood_egg_ticks: 20,
+            brood_larva_ticks: 20,
+            ..SimConfig::small()
+        });
+        sim.colonies[0].food_stored = 100.0;
+        sim.colonies[0].brood.push(Brood {
+            stage: BroodStage::Egg,
+            role: ROLE_WORKER,
+            ticks: 0,
+        });
+        let ants_before = sim.ants.count;
+        let old_ids = sim.ants.id.clone();
+
+        for _ in 0..200 {
+            sim.tick();
+            if sim.colonies[0].brood.is_empty() {
+                break;
+            }
+        }
+        assert!(sim.colonies[0].brood.is_empty(), "the egg never hatched");
+        assert_eq!(sim.ants.count, ants_before + 1);
+        let slot = sim
+            .ants
+            .id
+            .iter()
+            .position(|a| !old_ids.contains(a))
+            .unwrap();
+        let id = sim.ants.id[slot];
+        assert_eq!(sim.ants.colony_id[slot], sim.colonies[0].id);
+        assert_eq!(sim.ants.ant_type[slot], ROLE_WORKER);
+
+        let start = (sim.ants.pos_x[slot], sim.ants.pos_y[slot]);
+        for _ in 0..30 {
+            sim.tick();
+        }
+        let slot = sim.ants.id.iter().position(|&a| a == id).unwrap();
+        let end = (sim.ants.pos_x[slot], sim.ants.pos_y[slot]);
+        assert!(dist_sq(start, end) > 1.0, "the new ant never moved");
+    }
+
+    #[test]
+    fn food_ids_are_not_reused_across_removal_or_restore() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        let first = sim.spawn_food(300.0, 250.0, 100.0).unwrap();
+        sim.food_sources.retain(|f| f.id != first);
+        let second = sim.spawn_food(300.0, 250.0, 100.0).unwrap();
+        assert!(second > first);
+
+        let mut restored = SimulationState::new(SimConfig::small());
+        restored.restore_from_checkpoint(sim.to_checkpoint());
+        restored.food_sources.clear();
+        let third = restored.spawn_food(300.0, 250.0, 100.0).unwrap();
+        assert!(third > second);
+    }
+
+    #[test]
+    fn rect_queries_keep_what_is_inside_or_on_the_edge() {
+        let mut sim = empty_sim(SimConfig::small());
+        let rect = Rect {
+            min_x: 100.0,
+            min_y: 100.0,
+            max_x: 200.0,
+            max_y: 180.0,
+        };
+        let points = [
+            (150.0, 150.0), // inside
+            (100.0, 100.0), // on the min corner
+            (200.0, 140.0), // on the max x edge
+            (160.0, 180.0), // on the max y edge
+            (200.5, 150.0), // just right of it
+            (150.0, 99.5),  // just above
+            (600.0, 450.0), // far away
+        ];
+        for (k, &(x, y)) in points.iter().enumerate() {
+            sim.ants.add(x, y, 0, ROLE_WORKER, 1.0, 0.0);
+            sim.food_sources.push(food_at(k as u32, x, y));
+        }
+        assert_eq!(sim.ants_in_rect(&rect), [0, 1, 2, 3]);
+        assert_eq!(sim.food_in_rect(&rect), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn a_hazard_kill_is_counted_as_a_hazard_death() {
+        let mut sim = SimulationState::new(SimConfig {
+            hazards: vec![Hazard {
+                kind: HazardKind::Fire,
+                x: 200.0,
+                y: 200.0,
+                radius: 40.0,
+                damage: 100_000.0,
+            }],
+            ..SimConfig::small()
+        });
+        let before = sim.ants.count;
+        assert_eq!(sim.spawn_ants_at(200.0, 200.0, 1, Some(ROLE_WORKER)), 1);
+        sim.tick();
+
+        assert_eq!(sim.ants.count, before);
+        let deaths = sim.colonies[0].mortality;
+        assert_eq!(deaths.hazard, 1);
+        assert_eq!(deaths.starvation + deaths.old_age, 0);
+    }
+
+    #[test]
+    fn idle_workers_feed_larvae_when_no_nurses_are_assigned() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_spawn_interval: 1_000_000,
+            brood_larva_ticks: 20,
+            ..SimConfig::small()
+        });
+        assert_eq!(sim.config.worker_nurse_share, 0.0);
+        sim.colonies[0].food_stored = 100.0;
+        sim.colonies[0].brood.push(Brood {
+            stage: BroodStage::Larva,
+            role: ROLE_WORKER,
+            ticks: 0,
+        });
+        for _ in 0..40 {
+            sim.tick();
+        }
+        assert!(sim.colonies[0].brood.is_empty(), "the larva never hatched");
+        assert!((0..sim.ants.count).all(|i| sim.ants.task[i] == TASK_FORAGER));
+    }
+
+    #[test]
+    fn steering_without_a_direction_holds_course() {
+        assert_eq!(steer_heading(0.0, 1.0, 0.3), std::f32::consts::FRAC_PI_2);
+        assert_eq!(steer_heading(0.0, 0.0, 2.0), 2.0);
+        assert_eq!(steer_heading(1e-5, -1e-5, 2.0), 2.0);
+        assert_eq!(steer_heading(f32::NAN, 1.0, -1.0), -1.0);
+        assert_eq!(steer_heading(f32::INFINITY, f32::NEG_INFINITY, -1.0), -1.0);
+
+        // an ant standing in a danger cell whose avoidance blows the
+        // steering up keeps a usable heading and position
+        let mut sim = empty_sim(SimConfig {
+            hazards: vec![Hazard {
+                kind: HazardKind::Pesticide,
+                x: 300.0,
+                y: 300.0,
+                radius: 30.0,
+                damage: 0.0,
+            }],
+            hazard_avoid_weight: f32::INFINITY,
+            ..SimConfig::small()
+        });
+        let speed = sim.config.ant_max_speed;
+        sim.ants
+            .add(300.0, 300.0, sim.colonies[0].id, ROLE_WORKER, speed, 0.0);
+        for _ in 0..30 {
+            sim.tick();
+            let (x, y, h) = (sim.ants.pos_x[0], sim.ants.pos_y[0], sim.ants.heading[0]);
+            assert!(
+                x.is_finite() && y.is_finite() && h.is_finite(),
+                "({x}, {y}) {h}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_hazard_only_hurts_ants_inside_it() {
+        let mut sim = empty_sim(SimConfig {
+            hazards: vec![Hazard {
+                kind: HazardKind::Pesticide,
+                x: 200.0,
+                y: 200.0,
+                radius: 60.0,
+                damage: 30.0,
+            }],
+            ..SimConfig::small()
+        });
+        let inside = sim.ants.add(200.0, 200.0, 0, ROLE_WORKER, 0.0, 0.0);
+        let outside = sim.ants.add(400.0, 200.0, 0, ROLE_WORKER, 0.0, 0.0);
+        sim.tick();
+
+        let health = |id: u32| sim.ants.health[sim.ants.id.iter().position(|&a| a == id).unwrap()];
+        assert!(health(inside) < 100.0);
+        assert_eq!(health(outside), 100.0);
+    }
+
+    #[test]
+    fn spawn_food_places_a_source_at_the_position() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        let id = sim.spawn_food(300.0, 250.0, 400.0).unwrap();
+        let food = sim.food_sources.iter().find(|f| f.id == id).unwrap();
+        assert_eq!((food.x, food.y), (300.0, 250.0));
+        assert_eq!(food.amount, 400.0);
+        assert_eq!(food.max_amount, 400.0);
+        assert!(sim.take_world_changed());
+    }
+
+    #[test]
+    fn affordable_ant_count_at_a_flat_price() {
+        let cfg = SimConfig {
+            colony_spawn_cost: 2.0,
+            colony_spawn_cost_growth: 0.0,
+            ..SimConfig::small()
+        };
+        assert_eq!(affordable_ant_count(6.0, &cfg, 10, 5), 3);
+        assert_eq!(affordable_ant_count(7.9, &cfg, 10, 5), 3);
+        assert_eq!(affordable_ant_count(100.0, &cfg, 10, 5), 5);
+        assert_eq!(affordable_ant_count(1.9, &cfg, 10, 5), 0);
+        assert_eq!(affordable_ant_count(-4.0, &cfg, 10, 5), 0);
+        assert_eq!(affordable_ant_count(f32::NAN, &cfg, 10, 5), 0);
+
+        let free = SimConfig {
+            colony_spawn_cost: 0.0,
+            ..cfg
+        };
+        assert_eq!(affordable_ant_count(0.0, &free, 10, 5), 5);
+    }
+
+    #[test]
+    fn weighted_choice_draws_in_proportion_to_weight() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let weights = [6.0, 0.0, 3.0, 1.0, -2.0];
+        let mut counts = [0usize; 5];
+        let draws = 20_000;
+        for _ in 0..draws {
+            counts[weighted_choice(&mut rng, weights.iter().copied()).unwrap()] += 1;
+        }
+        let share = |i: usize| counts[i] as f32 / draws as f32;
+        assert!((share(0) - 0.6).abs() < 0.02, "{counts:?}");
+        assert!((share(2) - 0.3).abs() < 0.02, "{counts:?}");
+        assert!((share(3) - 0.1).abs() < 0.02, "{counts:?}");
+        assert_eq!(counts[1] + counts[4], 0);
+        assert_eq!(weighted_choice(&mut rng, [0.0, -1.0].into_iter()), None);
+    }
+
+    #[test]
+    fn a_half_soldier_mix_spawns_about_half_soldiers() {
+        let mix = [0.5, 0.0, 0.5];
+        let mut rng = SmallRng::seed_from_u64(11);
+        let draws = 10_000;
+        let soldiers = (0..draws)
+            .filter(|_| random_role(&mut rng, &mix) == ROLE_SOLDIER)
+            .count();
+        let share = soldiers as f32 / draws as f32;
+        assert!((share - 0.5).abs() < 0.02, "{share}");
+
+        // and so does a starting population at neutral aggression
+        let sim = SimulationState::new(SimConfig {
+            role_mix: mix,
+            colony_aggression: 0.5,
+            colony_starting_population: 2000,
+            max_ants: 2000,
+            ..SimConfig::small()
+        });
+        assert_eq!(sim.ants.count, 2000);
+        let soldiers = sim.ants.ant_type[..sim.ants.count]
+            .iter()
+            .filter(|&&r| r == ROLE_SOLDIER)
+            .count();
+        let share = soldiers as f32 / 2000.0;
+        assert!((share - 0.5).abs() < 0.04, "{share}");
+        assert!(!sim.ants.ant_type.contains(&ROLE_SCOUT));
+    }
+
+    #[test]
+    fn food_sources_remember_their_kind() {
+        let cfg: SimConfig = toml::from_str(
+            r#"
+            [[food_kinds]]
+            name = "seeds"
+            weight = 1.0
+            nutrition = 1.0
+
+            [[food_kinds]]
+            name = "honeydew"
+            weight = 1.0
+            nutrition = 3.0
+            "#,
+        )
+        .unwrap();
+        let mut sim = SimulationState::new(SimConfig {
+            food_kinds: cfg.food_kinds,
+            ..SimConfig::small()
+        });
+        for k in 0..20 {
+            sim.spawn_food(100.0 + k as f32 * 40.0, 100.0, 50.0);
+        }
+        let kinds: Vec<usize> = sim.food_sources.iter().map(|f| f.kind.unwrap()).collect();
+        assert!(kinds.contains(&0) && kinds.contains(&1));
+        for f in &sim.food_sources {
+            assert_eq!(
+                f.nutrition,
+                sim.config.food_kinds[f.kind.unwrap()].nutrition
+            );
+        }
+    }
+
+    #[test]
+    fn four_colonies_get_their_own_nest_and_hue() {
+        for layout in [ColonyLayout::Line, ColonyLayout::Ring, ColonyLayout::Grid] {
+            let sim = SimulationState::new(SimConfig {
+                colony_count: 4,
+                colony_layout: layout,
+                ..SimConfig::small()
+            });
+            assert_eq!(sim.colonies.len(), 4);
+            for (k, a) in sim.colonies.iter().enumerate() {
+                for b in &sim.colonies[k + 1..] {
+                    assert!(
+                        dist_sq((a.x, a.y), (b.x, b.y)) > (2.0 * a.radius).powi(2),
+                        "{layout:?}: nests {} and {} overlap",
+                        a.id,
+                        b.id
+                    );
+                    assert_ne!(a.color_hue, b.color_hue, "{layout:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_nests_are_pushed_apart() {
+        let cfg = SimConfig {
+            colony_spacing: 250.0,
+            ..SimConfig::small()
+        };
+        // two on the same spot, one just beside them
+        let mut nests = [(600.0, 450.0), (600.0, 450.0), (640.0, 460.0)];
+        separate_nests(&mut nests, &cfg);
+        for a in 0..nests.len() {
+            for b in a + 1..nests.len() {
+                let d = dist_sq(nests[a], nests[b]).sqrt();
+                assert!(d >= 250.0 - 0.5, "nests {a} and {b} are {d} apart");
+            }
+        }
+    }
+
+    #[test]
+    fn eggs_cost_more_in_a_bigger_colony() {
+        let cfg = SimConfig {
+            colony_spawn_cost: 2.0,
+            colony_spawn_cost_growth: 1.0,
+            colony_max_population: 200,
+            ..SimConfig::small()
+        };
+        // the 10th and the 100th ant, each laid as a single egg
+        let tenth = egg_cost(&cfg, 9, 1);
+        let hundredth = egg_cost(&cfg, 99, 1);
+        assert!(hundredth > tenth, "{hundredth} <= {tenth}");
+        assert_eq!(egg_cost(&cfg, 0, 1), 2.0);
+
+        // a batch costs the same as laying its eggs one by one
+        let batch = egg_cost(&cfg, 9, 3);
+        let single: f32 = (9..12).map(|size| egg_cost(&cfg, size, 1)).sum();
+        assert!((batch - single).abs() < 1e-4);
+
+        // and a bigger colony affords fewer eggs from the same store
+        assert!(
+            affordable_ant_count(50.0, &cfg, 99, 100) < affordable_ant_count(50.0, &cfg, 9, 100)
+        );
+    }
+
+    #[test]
+    fn a_worker_filled_to_capacity_heads_home() {
+        // ten pickups of 0.3 come to a hair under 3.0 in f32
+        let mut sim = forager_on_food(SimConfig {
+            ant_carry_capacity: 3.0,
+            ant_return_fill: 1.0,
+            food_pickup_amount: 0.3,
+            ..SimConfig::small()
+        });
+        for _ in 0..10 {
+            assert_eq!(sim.ants.state[0], AntState::Foraging);
+            sim.tick();
+        }
+        assert_eq!(sim.ants.cargo[0], 3.0);
+        assert_eq!(sim.ants.state[0], AntState::Returning);
+        assert!((sim.food_sources[0].amount - 97.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn a_strong_own_trail_damps_the_levy_chance() {
+        let mut sim = empty_sim(SimConfig {
+            levy_probability: 0.5,
+            levy_trail_damping: 0.8,
+            rival_trail_weight: 0.0,
+            ..SimConfig::small()
+        });
+        let own = sim.colonies[0].id;
+        sim.ants.add(300.0, 300.0, own, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.add(900.0, 300.0, own, ROLE_WORKER, 0.0, 0.0);
+        sim.ants.add(300.0, 700.0, own, ROLE_WORKER, 0.0, 0.0);
+        sim.pheromones.deposit_food(300.0, 300.0, 0.0, 1.0, own);
+        sim.pheromones.deposit_food(300.0, 700.0, 0.0, 1.0, own + 1);
+
+        let on_trail = levy_chance(&sim.config, &sim.pheromones, &sim.ants, 0);
+        let bare = levy_chance(&sim.config, &sim.pheromones, &sim.ants, 1);
+        let on_rival = levy_chance(&sim.config, &sim.pheromones, &sim.ants, 2);
+        assert_eq!(bare, 0.5);
+        assert!(on_trail < bare, "{on_trail} !< {bare}");
+        assert!((on_trail - 0.5 * 0.2).abs() < 1e-4, "{on_trail}");
+        // a rival's trail gives no confidence when rival trails are ignored
+        assert_eq!(on_rival, bare);
     }
 }
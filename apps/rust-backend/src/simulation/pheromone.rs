@@ -1,11 +1,20 @@
+use std::ops::Range;
+
 use serde::{Deserialize, Serialize};
 
+use super::geometry::Rect;
+use super::hazard::Hazard;
 use super::terrain::Terrain;
 
+/// `food_owner` of a cell no colony has laid trail in.
+pub const NO_OWNER: u32 = u32::MAX;
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum PheromoneType {
     Food,
     Home,
+    Danger,
+    Queen,
 }
 
 /// Grid-based pheromone field with separate layers per type.
@@ -16,7 +25,25 @@ pub struct PheromoneField {
     pub grid_h: usize,
     pub cell_size: f32,
     pub food: Vec<f32>,
+    /// Accumulated travel direction of the ants that laid the food trail in
+    /// each cell (weighted by amount, decays with the trail). Returning ants
+    /// deposit while heading home, so this points away from the food.
+    pub food_dir_x: Vec<f32>,
+    pub food_dir_y: Vec<f32>,
+    /// Colony whose trail dominates each food cell (`NO_OWNER` if none has
+    /// laid any); foragers discount trail owned by other colonies.
+    pub food_owner: Vec<u32>,
     pub home: Vec<f32>,
+    /// Colony whose nest the home gradient in each cell leads to (the
+    /// nearest one, `NO_OWNER` in blocked cells).
+    pub home_owner: Vec<u32>,
+    /// Static warning field around hazards: 1 inside, fading to 0 at the
+    /// edge of the warning band.
+    pub danger: Vec<f32>,
+    /// Scent of the colonies' queens, re-emitted around every inhabited nest
+    /// each tick (see `emit_queen`); evaporates with the food layer, so the
+    /// scent of a colony that dies out fades away.
+    pub queen: Vec<f32>,
     /// 1 = cell lies inside solid terrain; no deposit or diffusion there.
     pub blocked: Vec<u8>,
 }
@@ -31,7 +58,13 @@ impl PheromoneField {
             grid_h,
             cell_size,
             food: vec![0.0; size],
+            food_dir_x: vec![0.0; size],
+            food_dir_y: vec![0.0; size],
+            food_owner: vec![NO_OWNER; size],
             home: vec![0.0; size],
+            home_owner: vec![NO_OWNER; size],
+            danger: vec![0.0; size],
+            queen: vec![0.0; size],
             blocked: vec![0; size],
         }
     }
@@ -50,13 +83,15 @@ impl PheromoneField {
     /// Seed a static radial "home" gradient anchored to the colonies. The field
     /// is strongest at a nest and falls off linearly with distance, so a
     /// returning ant can always climb the gradient back toward the nearest nest.
+    /// `home_owner` records which nest that is, so ants only trust the
+    /// gradient inside their own colony's catchment.
     ///
     /// Unlike the food layer, this is navigation infrastructure: it is computed
     /// once and intentionally excluded from evaporation and diffusion. This
     /// replaces the previous behavior where wandering ants emitted home
     /// pheromone, which made the "home" field a population-density blob rather
     /// than real directional information.
-    pub fn seed_home_field(&mut self, colonies: &[(f32, f32)]) {
+    pub fn seed_home_field(&mut self, colonies: &[(u32, f32, f32)]) {
         if colonies.is_empty() {
             return;
         }
@@ -71,21 +106,48 @@ impl PheromoneField {
                 let idx = self.idx(gx, gy);
                 if self.blocked[idx] == 1 {
                     self.home[idx] = 0.0;
+                    self.home_owner[idx] = NO_OWNER;
                     continue;
                 }
                 let cx = (gx as f32 + 0.5) * self.cell_size;
                 let cy = (gy as f32 + 0.5) * self.cell_size;
                 let mut best = 0.0f32;
-                for &(colx, coly) in colonies {
+                let mut owner = NO_OWNER;
+                for &(id, colx, coly) in colonies {
                     let dx = cx - colx;
                     let dy = cy - coly;
                     let d = (dx * dx + dy * dy).sqrt();
                     let v = (1.0 - d / range).max(0.0);
                     if v > best {
                         best = v;
+                        owner = id;
                     }
                 }
                 self.home[idx] = best;
+                self.home_owner[idx] = owner;
+            }
+        }
+    }
+
+    /// Seed the danger field around hazards. Like the home field it is
+    /// computed once and neither evaporates nor diffuses.
+    pub fn seed_danger_field(&mut self, hazards: &[Hazard], warning_distance: f32) {
+        let band = warning_distance.max(1.0);
+        for gy in 0..self.grid_h {
+            for gx in 0..self.grid_w {
+                let idx = self.idx(gx, gy);
+                if self.blocked[idx] == 1 {
+                    continue;
+                }
+                let cx = (gx as f32 + 0.5) * self.cell_size;
+                let cy = (gy as f32 + 0.5) * self.cell_size;
+                self.danger[idx] = hazards
+                    .iter()
+                    .map(|h| {
+                        let d = ((cx - h.x).powi(2) + (cy - h.y).powi(2)).sqrt() - h.radius;
+                        (1.0 - d / band).clamp(0.0, 1.0)
+                    })
+                    .fold(0.0, f32::max);
             }
         }
     }
@@ -94,6 +156,8 @@ impl PheromoneField {
         match ptype {
             PheromoneType::Food => &self.food,
             PheromoneType::Home => &self.home,
+            PheromoneType::Danger => &self.danger,
+            PheromoneType::Queen => &self.queen,
         }
     }
 
@@ -112,28 +176,89 @@ impl PheromoneField {
         gy * self.grid_w + gx
     }
 
-    pub fn deposit(&mut self, x: f32, y: f32, ptype: PheromoneType, amount: f32) {
-        if let Some((gx, gy)) = self.to_grid(x, y) {
-            let idx = self.idx(gx, gy);
-            if self.blocked[idx] == 1 {
-                return;
+    /// Lay food trail at (x, y). `heading` is the depositing ant's direction of
+    /// travel, recorded so followers can tell which way the trail runs. The
+    /// cell passes to `colony` once its deposit outweighs what is left of
+    /// another colony's trail there.
+    pub fn deposit_food(&mut self, x: f32, y: f32, heading: f32, amount: f32, colony: u32) {
+        let Some(idx) = self.cell_index(x, y) else {
+            return;
+        };
+        if self.blocked[idx] == 1 {
+            return;
+        }
+        if self.food_owner[idx] != colony && amount >= self.food[idx] {
+            self.food_owner[idx] = colony;
+        }
+        self.food[idx] = (self.food[idx] + amount).min(1.0);
+        self.food_dir_x[idx] += heading.cos() * amount;
+        self.food_dir_y[idx] += heading.sin() * amount;
+    }
+
+    /// Queen emission: raise the queen scent toward a cone that is
+    /// `strength` at the nest center and fades to nothing at `range`.
+    pub fn emit_queen(&mut self, x: f32, y: f32, range: f32, strength: f32) {
+        let r = (range / self.cell_size).ceil() as i32;
+        let (gx, gy) = ((x / self.cell_size) as i32, (y / self.cell_size) as i32);
+        for cy in (gy - r).max(0)..=(gy + r).min(self.grid_h as i32 - 1) {
+            for cx in (gx - r).max(0)..=(gx + r).min(self.grid_w as i32 - 1) {
+                let idx = self.idx(cx as usize, cy as usize);
+                if self.blocked[idx] == 1 {
+                    continue;
+                }
+                let px = (cx as f32 + 0.5) * self.cell_size;
+                let py = (cy as f32 + 0.5) * self.cell_size;
+                let d = ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+                let v = strength * (1.0 - d / range);
+                if v > self.queen[idx] {
+                    self.queen[idx] = v;
+                }
             }
-            let layer = match ptype {
-                PheromoneType::Food => &mut self.food,
-                PheromoneType::Home => &mut self.home,
-            };
-            layer[idx] = (layer[idx] + amount).min(1.0);
         }
     }
 
+    #[inline]
+    fn cell_index(&self, x: f32, y: f32) -> Option<usize> {
+        self.to_grid(x, y).map(|(gx, gy)| self.idx(gx, gy))
+    }
+
+    /// Colony the home gradient at (x, y) leads to, `NO_OWNER` off-grid.
+    pub fn home_owner_at(&self, x: f32, y: f32) -> u32 {
+        self.cell_index(x, y)
+            .map_or(NO_OWNER, |idx| self.home_owner[idx])
+    }
+
     pub fn sample(&self, x: f32, y: f32, ptype: PheromoneType) -> f32 {
-        if let Some((gx, gy)) = self.to_grid(x, y) {
-            self.layer(ptype)[self.idx(gx, gy)]
-        } else {
-            0.0
+        match self.cell_index(x, y) {
+            Some(idx) => self.layer(ptype)[idx],
+            None => 0.0,
         }
     }
 
+    /// How much a forager at (x, y) can trust the food trail around it, 0 to
+    /// 1: the strongest trail in its cell and the eight next to it, with trail
+    /// owned by another colony counting `rival_weight` as much. Trail only
+    /// fades once laid, so a strong cell is also a fresh one.
+    pub fn trail_confidence(&self, x: f32, y: f32, colony: u32, rival_weight: f32) -> f32 {
+        let Some((gx, gy)) = self.to_grid(x, y) else {
+            return 0.0;
+        };
+        let mut best = 0.0f32;
+        for cy in gy.saturating_sub(1)..=(gy + 1).min(self.grid_h - 1) {
+            for cx in gx.saturating_sub(1)..=(gx + 1).min(self.grid_w - 1) {
+                let idx = self.idx(cx, cy);
+                let owner = self.food_owner[idx];
+                let v = if owner == NO_OWNER || owner == colony {
+                    self.food[idx]
+                } else {
+                    self.food[idx] * rival_weight
+                };
+                best = best.max(v);
+            }
+        }
+        best.clamp(0.0, 1.0)
+    }
+
     /// Three-sensor biological model: sample left, center, right ahead of the ant.
     /// Returns the angle toward the strongest pheromone signal, or None if nothing detected.
     pub fn sense_direction(
@@ -145,95 +270,464 @@ impl PheromoneField {
         sensor_angle: f32,
         ptype: PheromoneType,
     ) -> Option<f32> {
-        let left = heading - sensor_angle;
-        let center = heading;
-        let right = heading + sensor_angle;
-
-        let sl = self.sample(
-            x + left.cos() * sensor_dist,
-            y + left.sin() * sensor_dist,
-            ptype,
-        );
-        let sc = self.sample(
-            x + center.cos() * sensor_dist,
-            y + center.sin() * sensor_dist,
-            ptype,
-        );
-        let sr = self.sample(
-            x + right.cos() * sensor_dist,
-            y + right.sin() * sensor_dist,
-            ptype,
-        );
+        sense_with(heading, sensor_angle, |angle| {
+            let (sx, sy) = (x + angle.cos() * sensor_dist, y + angle.sin() * sensor_dist);
+            if !self.line_clear(x, y, sx, sy) {
+                return 0.0;
+            }
+            self.sample(sx, sy, ptype)
+        })
+    }
 
-        let max_val = sl.max(sc).max(sr);
-        if max_val < 0.001 {
-            return None;
-        }
+    /// `sense_direction` on the food layer for an ant heading *toward* food.
+    /// Trail whose recorded direction matches the sensor's direction was laid
+    /// by ants walking home, so following it would lead away from the food; it
+    /// is discounted by up to `direction_bias` (0 = ignore direction). Trail
+    /// owned by a colony other than `colony` counts `rival_weight` as much.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sense_food_trail(
+        &self,
+        x: f32,
+        y: f32,
+        heading: f32,
+        sensor_dist: f32,
+        sensor_angle: f32,
+        direction_bias: f32,
+        colony: u32,
+        rival_weight: f32,
+    ) -> Option<f32> {
+        self.sense_trail(
+            x,
+            y,
+            heading,
+            sensor_dist,
+            sensor_angle,
+            direction_bias,
+            colony,
+            rival_weight,
+            false,
+        )
+    }
 
-        if sc >= sl && sc >= sr {
-            Some(center)
-        } else if sl > sr {
-            Some(left)
-        } else {
-            Some(right)
-        }
+    /// `sense_food_trail` for an ant carrying food home: here trail running
+    /// the way the sensor points is the one to follow, and trail running
+    /// against it (toward the food) is discounted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sense_home_trail(
+        &self,
+        x: f32,
+        y: f32,
+        heading: f32,
+        sensor_dist: f32,
+        sensor_angle: f32,
+        direction_bias: f32,
+        colony: u32,
+        rival_weight: f32,
+    ) -> Option<f32> {
+        self.sense_trail(
+            x,
+            y,
+            heading,
+            sensor_dist,
+            sensor_angle,
+            direction_bias,
+            colony,
+            rival_weight,
+            true,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn sense_trail(
+        &self,
+        x: f32,
+        y: f32,
+        heading: f32,
+        sensor_dist: f32,
+        sensor_angle: f32,
+        direction_bias: f32,
+        colony: u32,
+        rival_weight: f32,
+        homeward: bool,
+    ) -> Option<f32> {
+        sense_with(heading, sensor_angle, |angle| {
+            let (ax, ay) = (angle.cos(), angle.sin());
+            let (sx, sy) = (x + ax * sensor_dist, y + ay * sensor_dist);
+            if !self.line_clear(x, y, sx, sy) {
+                return 0.0;
+            }
+            let Some(idx) = self.cell_index(sx, sy) else {
+                return 0.0;
+            };
+            let owner = self.food_owner[idx];
+            let v = if owner == NO_OWNER || owner == colony {
+                self.food[idx]
+            } else {
+                self.food[idx] * rival_weight
+            };
+            let (dx, dy) = (self.food_dir_x[idx], self.food_dir_y[idx]);
+            let mag = (dx * dx + dy * dy).sqrt();
+            if mag < 1e-6 {
+                return v;
+            }
+            // +1 = trail runs the way this sensor points (i.e. homeward)
+            let along = (ax * dx + ay * dy) / mag;
+            let wrong_way = if homeward { -along } else { along };
+            v * (1.0 - direction_bias * (1.0 + wrong_way) * 0.5)
+        })
     }
 
-    /// Evaporate the food (recruitment) layer. The home layer is a static
-    /// gradient seeded by `seed_home_field` and is intentionally left untouched.
+    /// True if no blocked cell lies between the two points, so a sensor can't
+    /// pick up trail on the far side of a wall.
+    fn line_clear(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let steps = ((dx * dx + dy * dy).sqrt() / (self.cell_size * 0.5)).ceil() as usize;
+        (1..=steps).all(|k| {
+            let t = k as f32 / steps as f32;
+            self.cell_index(x0 + dx * t, y0 + dy * t)
+                .is_none_or(|idx| self.blocked[idx] == 0)
+        })
+    }
+
+    /// Columns and rows of the cells that overlap `rect`, a cell touching it
+    /// only along an edge included; `None` if it misses the grid.
+    pub fn cells_in_rect(&self, rect: &Rect) -> Option<(Range<usize>, Range<usize>)> {
+        let span = |min: f32, max: f32, cells: usize| {
+            let (min, max) = (min / self.cell_size, max / self.cell_size);
+            let last_cell = cells as f32 - 1.0;
+            (max >= 0.0 && min <= cells as f32).then(|| {
+                let first = min.floor().clamp(0.0, last_cell) as usize;
+                let last = max.floor().clamp(0.0, last_cell) as usize;
+                first..last + 1
+            })
+        };
+        let xs = span(rect.min_x, rect.max_x, self.grid_w)?;
+        let ys = span(rect.min_y, rect.max_y, self.grid_h)?;
+        Some((xs, ys))
+    }
+
+    /// Number of food-layer cells carrying a visible trail (at least one
+    /// quantization step once encoded for the wire).
+    pub fn active_food_cells(&self) -> usize {
+        self.food.iter().filter(|&&v| v >= 1.0 / 255.0).count()
+    }
+
+    /// Evaporate the food (recruitment) and queen layers. The home layer is a
+    /// static gradient seeded by `seed_home_field` and is intentionally left
+    /// untouched.
     pub fn evaporate(&mut self, factor: f32) {
-        for v in &mut self.food {
+        for v in self
+            .food
+            .iter_mut()
+            .chain(&mut self.food_dir_x)
+            .chain(&mut self.food_dir_y)
+            .chain(&mut self.queen)
+        {
             *v *= factor;
         }
     }
 
+    /// Shift the food layer (and its trail directions) by a world-space offset,
+    /// as wind would. Each cell takes the value found upwind of it, so trails
+    /// drift and blur; nothing is blown into or out of solid cells. A cell
+    /// passes to the colony whose trail blew the most into it.
+    pub fn advect(&mut self, shift_x: f32, shift_y: f32) {
+        let (sx, sy) = (shift_x / self.cell_size, shift_y / self.cell_size);
+        let (w, h) = (self.grid_w, self.grid_h);
+        let (strength, owners) = (self.food.clone(), self.food_owner.clone());
+        for layer in [&mut self.food, &mut self.food_dir_x, &mut self.food_dir_y] {
+            let src = layer.to_vec();
+            for y in 0..h {
+                for x in 0..w {
+                    let idx = y * w + x;
+                    if self.blocked[idx] == 1 {
+                        continue;
+                    }
+                    layer[idx] =
+                        sample_bilinear(&src, &self.blocked, w, h, x as f32 - sx, y as f32 - sy);
+                }
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                let idx = y * w + x;
+                if self.blocked[idx] == 1 {
+                    continue;
+                }
+                let taps = bilinear_taps(&self.blocked, w, h, x as f32 - sx, y as f32 - sy);
+                self.food_owner[idx] =
+                    strongest_owner(taps.map(|(i, wt)| (owners[i], strength[i] * wt)));
+            }
+        }
+    }
+
     /// Diffuse the food layer only; the home layer is static infrastructure.
+    /// Trail directions spread with the same kernel; they are stored weighted
+    /// by the amount deposited, so stronger trail carries its direction
+    /// further into a blended cell. Ownership goes the same way: a blended
+    /// cell passes to the colony contributing the most trail to it.
     pub fn diffuse(&mut self, rate: f32) {
-        diffuse_layer(&mut self.food, &self.blocked, self.grid_w, self.grid_h, rate);
+        let strength = self.food.clone();
+        let owners = self.food_owner.clone();
+        let (w, h) = (self.grid_w, self.grid_h);
+        for layer in [&mut self.food, &mut self.food_dir_x, &mut self.food_dir_y] {
+            diffuse_layer(layer, &strength, &self.blocked, w, h, rate);
+        }
+        for y in 0..h {
+            for x in 0..w {
+                let idx = y * w + x;
+                if self.blocked[idx] == 1 || strength[idx] < 0.0001 {
+                    continue;
+                }
+                let neighbours = open_neighbours(&self.blocked, w, h, x, y);
+                let share = rate / neighbours.clone().count().max(1) as f32;
+                let kept = (owners[idx], strength[idx] * (1.0 - rate));
+                let spread = neighbours.map(|n| (owners[n], strength[n] * share));
+                self.food_owner[idx] = strongest_owner(std::iter::once(kept).chain(spread));
+            }
+        }
+    }
+}
+
+/// Owner of the largest `(owner, trail)` contribution, `NO_OWNER` if none
+/// brings any trail.
+fn strongest_owner(contributions: impl Iterator<Item = (u32, f32)>) -> u32 {
+    let mut best = (NO_OWNER, 0.0f32);
+    for (owner, trail) in contributions {
+        if trail > best.1 {
+            best = (owner, trail);
+        }
+    }
+    best.0
+}
+
+/// The open in-grid cells a bilinear sample at fractional grid coordinates
+/// reads, with their weights.
+fn bilinear_taps(
+    blocked: &[u8],
+    w: usize,
+    h: usize,
+    fx: f32,
+    fy: f32,
+) -> impl Iterator<Item = (usize, f32)> + '_ {
+    let (x0, y0) = (fx.floor(), fy.floor());
+    let (tx, ty) = (fx - x0, fy - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+    [
+        (x0, y0, (1.0 - tx) * (1.0 - ty)),
+        (x0 + 1, y0, tx * (1.0 - ty)),
+        (x0, y0 + 1, (1.0 - tx) * ty),
+        (x0 + 1, y0 + 1, tx * ty),
+    ]
+    .into_iter()
+    .filter(move |&(x, y, _)| x >= 0 && y >= 0 && (x as usize) < w && (y as usize) < h)
+    .map(move |(x, y, wt)| (y as usize * w + x as usize, wt))
+    .filter(|&(idx, _)| blocked[idx] == 0)
+}
+
+/// Bilinear sample of `layer` at fractional grid coordinates; blocked and
+/// out-of-grid cells read as empty.
+fn sample_bilinear(layer: &[f32], blocked: &[u8], w: usize, h: usize, fx: f32, fy: f32) -> f32 {
+    bilinear_taps(blocked, w, h, fx, fy)
+        .map(|(idx, wt)| layer[idx] * wt)
+        .sum()
+}
+
+/// Sample left/center/right sensor angles and pick the strongest.
+fn sense_with(heading: f32, sensor_angle: f32, sample: impl Fn(f32) -> f32) -> Option<f32> {
+    let left = heading - sensor_angle;
+    let center = heading;
+    let right = heading + sensor_angle;
+
+    let sl = sample(left);
+    let sc = sample(center);
+    let sr = sample(right);
+
+    let max_val = sl.max(sc).max(sr);
+    if max_val < 0.001 {
+        return None;
+    }
+
+    if sc >= sl && sc >= sr {
+        Some(center)
+    } else if sl > sr {
+        Some(left)
+    } else {
+        Some(right)
     }
 }
 
-fn diffuse_layer(layer: &mut [f32], blocked: &[u8], w: usize, h: usize, rate: f32) {
+/// Blend each cell of `layer` with its open neighbours. Only cells with
+/// trail `strength` are touched, so empty ground stays empty.
+fn diffuse_layer(
+    layer: &mut [f32],
+    strength: &[f32],
+    blocked: &[u8],
+    w: usize,
+    h: usize,
+    rate: f32,
+) {
     let src: Vec<f32> = layer.to_vec();
     let keep = 1.0 - rate;
 
     for y in 0..h {
         for x in 0..w {
             let idx = y * w + x;
-            if blocked[idx] == 1 {
+            if blocked[idx] == 1 || strength[idx] < 0.0001 {
                 continue;
             }
             let center = src[idx];
-            if center < 0.0001 {
-                continue;
-            }
 
             let mut sum = 0.0f32;
             let mut count = 0u32;
-
-            for dy in -1i32..=1 {
-                for dx in -1i32..=1 {
-                    if dx == 0 && dy == 0 {
-                        continue;
-                    }
-                    let nx = x as i32 + dx;
-                    let ny = y as i32 + dy;
-                    if nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h {
-                        let nidx = ny as usize * w + nx as usize;
-                        if blocked[nidx] == 0 {
-                            sum += src[nidx];
-                            count += 1;
-                        }
-                    }
-                }
+            for nidx in open_neighbours(blocked, w, h, x, y) {
+                sum += src[nidx];
+                count += 1;
             }
 
-            let avg_neighbor = if count > 0 {
-                sum / count as f32
-            } else {
-                0.0
-            };
+            let avg_neighbor = if count > 0 { sum / count as f32 } else { 0.0 };
             layer[idx] = center * keep + avg_neighbor * rate;
         }
     }
 }
+
+/// Indices of the open cells among the eight around (x, y).
+fn open_neighbours(
+    blocked: &[u8],
+    w: usize,
+    h: usize,
+    x: usize,
+    y: usize,
+) -> impl Iterator<Item = usize> + Clone + '_ {
+    (-1i32..=1)
+        .flat_map(|dy| (-1i32..=1).map(move |dx| (dx, dy)))
+        .filter(|&d| d != (0, 0))
+        .map(move |(dx, dy)| (x as i32 + dx, y as i32 + dy))
+        .filter(move |&(nx, ny)| nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h)
+        .map(move |(nx, ny)| ny as usize * w + nx as usize)
+        .filter(|&nidx| blocked[nidx] == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn cells_in_rect_cover_the_cells_the_rect_touches() {
+        let field = PheromoneField::new(400.0, 200.0, 10.0);
+        let rect = |min_x, min_y, max_x, max_y| Rect {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        };
+        // inside: the cells it overlaps
+        assert_eq!(
+            field.cells_in_rect(&rect(15.0, 25.0, 42.0, 38.0)),
+            Some((1..5, 2..4))
+        );
+        // edges on cell borders take in the cells starting there
+        assert_eq!(
+            field.cells_in_rect(&rect(10.0, 20.0, 40.0, 30.0)),
+            Some((1..5, 2..4))
+        );
+        // sticking out of the world: clamped to the grid
+        assert_eq!(
+            field.cells_in_rect(&rect(-50.0, 150.0, 1000.0, 1000.0)),
+            Some((0..40, 15..20))
+        );
+        // exactly on the far edge of the world: the last cells
+        assert_eq!(
+            field.cells_in_rect(&rect(400.0, 200.0, 400.0, 200.0)),
+            Some((39..40, 19..20))
+        );
+        // outside
+        assert_eq!(field.cells_in_rect(&rect(450.0, 0.0, 500.0, 50.0)), None);
+        assert_eq!(field.cells_in_rect(&rect(0.0, -50.0, 50.0, -1.0)), None);
+    }
+
+    #[test]
+    fn trail_behind_a_wall_is_not_sensed() {
+        let mut field = PheromoneField::new(200.0, 200.0, 10.0);
+        // trail 40 units east of the ant, laid by ants walking west
+        field.deposit_food(145.0, 105.0, PI, 1.0, 0);
+        let sense = |field: &PheromoneField| {
+            (
+                field.sense_direction(105.0, 105.0, 0.0, 40.0, 0.5, PheromoneType::Food),
+                field.sense_food_trail(105.0, 105.0, 0.0, 40.0, 0.5, 0.5, 0, 1.0),
+            )
+        };
+        assert_eq!(sense(&field), (Some(0.0), Some(0.0)));
+
+        // a blocked cell halfway between them
+        let wall = field.cell_index(125.0, 105.0).unwrap();
+        field.blocked[wall] = 1;
+        assert_eq!(sense(&field), (None, None));
+    }
+
+    #[test]
+    fn each_colony_turns_toward_its_own_trail_where_they_overlap() {
+        let mut field = PheromoneField::new(200.0, 200.0, 10.0);
+        let (x, y, dist, angle) = (105.0, 105.0, 40.0, 0.6);
+        let sensor = |a: f32| (x + a.cos() * dist, y + a.sin() * dist);
+        // both colonies walked through both cells; colony 0 mostly on the
+        // left, colony 1 mostly on the right
+        let (lx, ly) = sensor(-angle);
+        let (rx, ry) = sensor(angle);
+        field.deposit_food(lx, ly, PI, 0.6, 0);
+        field.deposit_food(lx, ly, PI, 0.3, 1);
+        field.deposit_food(rx, ry, PI, 0.6, 1);
+        field.deposit_food(rx, ry, PI, 0.3, 0);
+        assert_eq!(field.food_owner[field.cell_index(lx, ly).unwrap()], 0);
+        assert_eq!(field.food_owner[field.cell_index(rx, ry).unwrap()], 1);
+
+        let turn = |field: &PheromoneField, y: f32, colony: u32, rival_weight: f32| {
+            field.sense_food_trail(x, y, 0.0, dist, angle, 0.0, colony, rival_weight)
+        };
+        assert_eq!(turn(&field, y, 0, 0.5), Some(-angle));
+        assert_eq!(turn(&field, y, 1, 0.5), Some(angle));
+        // counting rival trail in full, the two sides look the same to both
+        assert_eq!(turn(&field, y, 0, 1.0), turn(&field, y, 1, 1.0));
+
+        // wind blows both trails a cell south and they blur: the cells they
+        // spread into keep the colony that laid them
+        field.advect(0.0, 10.0);
+        for _ in 0..5 {
+            field.diffuse(0.08);
+        }
+        for (idx, &trail) in field.food.iter().enumerate() {
+            if trail > 0.0 {
+                assert_ne!(
+                    field.food_owner[idx], NO_OWNER,
+                    "unowned trail in cell {idx}"
+                );
+            }
+        }
+        let y = y + 10.0;
+        assert_eq!(turn(&field, y, 0, 0.0), Some(-angle));
+        assert_eq!(turn(&field, y, 1, 0.0), Some(angle));
+    }
+
+    #[test]
+    fn diffusion_keeps_the_trail_pointing_food_ward() {
+        // Returning ants walked west from food in the east, laying trail.
+        let mut field = PheromoneField::new(400.0, 200.0, 10.0);
+        for step in 0..30 {
+            field.deposit_food(350.0 - step as f32 * 10.0, 105.0, PI, 0.5, 0);
+        }
+        // a faint cell beside the trail that has no direction of its own yet
+        let side = field.cell_index(205.0, 115.0).unwrap();
+        field.food[side] = 0.05;
+        for _ in 0..20 {
+            field.diffuse(0.2);
+        }
+        assert!(field.food_dir_x[side] < 0.0);
+
+        // A seeking ant on the trail, facing north, with sensors due west
+        // and due east, turns east: up the trail toward the food.
+        let turn = field.sense_food_trail(200.0, 105.0, -FRAC_PI_2, 20.0, FRAC_PI_2, 1.0, 0, 1.0);
+        let turn = turn.unwrap();
+        assert!(turn.cos() > 0.99, "turned to {turn}");
+    }
+}
@@ -1,5 +1,6 @@
 use std::f32::consts::PI;
 
+use super::geometry::distance_to_segment;
 use super::terrain::Terrain;
 
 /// Probe the terrain ahead of the ant (center + two whiskers). If a wall is
@@ -45,16 +46,24 @@ pub fn wall_avoidance(
     Some((away.cos(), away.sin()))
 }
 
+/// Tick rate the wander strengths are tuned for.
+const WANDER_REFERENCE_RATE: f32 = 60.0;
+/// Fraction of the wander offset kept after one second (0.92 per tick at 60 Hz).
+const WANDER_RETENTION_PER_SEC: f32 = 0.0067;
+
 /// Craig Reynolds-style wander: small random perturbation of heading each tick.
+/// The perturbation is a random walk (scaled by sqrt(dt)) and its decay is a
+/// per-second rate, so the motion is the same at any tick rate.
 /// Returns (dx, dy) unit direction vector.
 pub fn wander_direction(
     heading: f32,
     wander_angle: &mut f32,
     strength: f32,
     rng_val: f32,
+    dt: f32,
 ) -> (f32, f32) {
-    *wander_angle += (rng_val - 0.5) * strength;
-    *wander_angle *= 0.92;
+    *wander_angle += (rng_val - 0.5) * strength * (dt * WANDER_REFERENCE_RATE).sqrt();
+    *wander_angle *= WANDER_RETENTION_PER_SEC.powf(dt);
     let angle = heading + *wander_angle;
     (angle.cos(), angle.sin())
 }
@@ -70,6 +79,35 @@ pub fn seek(fx: f32, fy: f32, tx: f32, ty: f32) -> (f32, f32) {
     (dx / dist, dy / dist)
 }
 
+/// Douglas-Peucker simplification. A point is dropped only if it lies within
+/// `tolerance` of the segment joining the points kept around it and
+/// `shortcut_ok` accepts that segment (e.g. it doesn't cross a wall).
+pub fn simplify_path(
+    points: &[(f32, f32)],
+    tolerance: f32,
+    shortcut_ok: &impl Fn((f32, f32), (f32, f32)) -> bool,
+) -> Vec<(f32, f32)> {
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let (mut split, mut max_d) = (0, 0.0);
+    for (k, &p) in points.iter().enumerate().take(points.len() - 1).skip(1) {
+        let d = distance_to_segment(p, first, last);
+        if d > max_d {
+            (split, max_d) = (k, d);
+        }
+    }
+    if max_d <= tolerance && shortcut_ok(first, last) {
+        return vec![first, last];
+    }
+    let split = if split == 0 { points.len() / 2 } else { split };
+    let mut out = simplify_path(&points[..=split], tolerance, shortcut_ok);
+    out.pop();
+    out.extend(simplify_path(&points[split..], tolerance, shortcut_ok));
+    out
+}
+
 /// Soft repulsion force that grows quadratically as the agent approaches a world edge.
 pub fn boundary_avoidance(x: f32, y: f32, w: f32, h: f32, margin: f32) -> (f32, f32) {
     let mut fx = 0.0f32;
@@ -90,14 +128,42 @@ pub fn boundary_avoidance(x: f32, y: f32, w: f32, h: f32, margin: f32) -> (f32,
     (fx, fy)
 }
 
-/// Normalize an angle to [-PI, PI].
-pub fn normalize_angle(angle: f32) -> f32 {
-    let mut a = angle;
-    while a > PI {
-        a -= 2.0 * PI;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::geometry::angle_diff;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Mean straight-line distance covered in one second by wandering walkers
+    /// at unit speed, stepped at `rate` Hz and turning at most 4 rad/s, as
+    /// `compute_ant_move` integrates them.
+    fn mean_displacement(rate: u32) -> f32 {
+        const RUNS: u64 = 2_000;
+        let dt = 1.0 / rate as f32;
+        let mut total = 0.0;
+        for seed in 0..RUNS {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let (mut x, mut y, mut heading, mut wander) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+            for _ in 0..rate {
+                let (dx, dy) = wander_direction(heading, &mut wander, 0.6, rng.gen(), dt);
+                let turn = angle_diff(heading, dy.atan2(dx)).clamp(-4.0 * dt, 4.0 * dt);
+                heading += turn;
+                x += heading.cos() * dt;
+                y += heading.sin() * dt;
+            }
+            total += (x * x + y * y).sqrt();
+        }
+        total / RUNS as f32
     }
-    while a < -PI {
-        a += 2.0 * PI;
+
+    #[test]
+    fn wander_covers_the_same_ground_at_any_tick_rate() {
+        let slow = mean_displacement(30);
+        let fast = mean_displacement(120);
+        assert!(
+            (slow - fast).abs() / fast < 0.05,
+            "30 fps: {slow}, 120 fps: {fast}"
+        );
     }
-    a
 }
@@ -169,6 +169,17 @@ impl Terrain {
         self.is_solid_cell(gx, gy)
     }
 
+    /// True if the straight segment between two world positions crosses no
+    /// solid cell.
+    pub fn segment_clear(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let steps = ((dx * dx + dy * dy).sqrt() / (self.cell_size * 0.5)).ceil() as usize;
+        (0..=steps).all(|k| {
+            let t = k as f32 / steps.max(1) as f32;
+            !self.is_solid_at(x0 + dx * t, y0 + dy * t)
+        })
+    }
+
     /// Open up a circular clearing centered at world position (x, y).
     /// Never carves the 1-cell world border.
     pub fn carve_circle(&mut self, x: f32, y: f32, radius: f32) {
@@ -196,6 +207,16 @@ impl Terrain {
         }
     }
 
+    /// Open a straight corridor of the given half-width between two points.
+    pub fn carve_corridor(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, radius: f32) {
+        let len = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+        let steps = (len / (radius * 0.5).max(self.cell_size)).ceil() as usize;
+        for k in 0..=steps {
+            let t = k as f32 / steps.max(1) as f32;
+            self.carve_circle(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t, radius);
+        }
+    }
+
     /// Flood-fill (4-connectivity) from the given world position and turn every
     /// unreachable open cell into solid, guaranteeing full connectivity.
     pub fn fill_unreachable(&mut self, from_x: f32, from_y: f32) {
@@ -226,9 +247,9 @@ impl Terrain {
             }
         }
 
-        for i in 0..self.solid.len() {
-            if self.solid[i] == 0 && !visited[i] {
-                self.solid[i] = 1;
+        for (cell, &seen) in self.solid.iter_mut().zip(&visited) {
+            if *cell == 0 && !seen {
+                *cell = 1;
             }
         }
     }
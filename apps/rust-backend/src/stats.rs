@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::simulation::ant::{role_name, AntState};
+use crate::simulation::colony::{BroodStage, Mortality};
+use crate::simulation::SimulationState;
+
+/// Simulation-wide figures at one tick. This is the single source for the
+/// log line, the stats message, the DB stats row and the CSV time series.
+#[derive(Clone, Copy, Debug)]
+pub struct StatsSample {
+    pub tick: u64,
+    pub total_ants: usize,
+    /// Colonies with at least one living member.
+    pub active_colonies: usize,
+    pub total_food_collected: f32,
+    /// Food held in colony stores.
+    pub colony_food: f32,
+    /// Food left in the world's food sources.
+    pub world_food: f32,
+    /// Food-pheromone grid cells carrying a visible trail.
+    pub pheromone_count: usize,
+}
+
+impl StatsSample {
+    pub fn capture(sim: &SimulationState) -> Self {
+        Self {
+            tick: sim.tick_count,
+            total_ants: sim.ants.count,
+            active_colonies: sim.colonies.iter().filter(|c| c.population > 0).count(),
+            total_food_collected: sim.total_food_collected,
+            colony_food: sim.colonies.iter().map(|c| c.food_stored).sum(),
+            world_food: sim.food_sources.iter().map(|f| f.amount).sum(),
+            pheromone_count: sim.pheromones.active_food_cells(),
+        }
+    }
+}
+
+/// Per-colony figures, serialized as-is into the stats message and the
+/// colonies query; `db_json` gives the `colony_stats` column form.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColonySample {
+    pub id: u32,
+    pub population: usize,
+    pub food_stored: f32,
+    pub foragers: usize,
+    pub deposit_rate: f32,
+    pub avg_trip_ticks: Option<f32>,
+    pub eggs: usize,
+    pub larvae: usize,
+    pub deaths: Mortality,
+}
+
+impl ColonySample {
+    pub fn capture_all(sim: &SimulationState) -> Vec<Self> {
+        sim.colonies
+            .iter()
+            .map(|c| Self {
+                id: c.id,
+                population: c.population,
+                food_stored: c.food_stored,
+                foragers: c.foraging.foragers,
+                deposit_rate: c.foraging.deposit_rate,
+                avg_trip_ticks: c.foraging.avg_trip_ticks,
+                eggs: c.brood_count(BroodStage::Egg),
+                larvae: c.brood_count(BroodStage::Larva),
+                deaths: c.mortality,
+            })
+            .collect()
+    }
+
+    /// The `colony_stats` column. Stored rows have always used snake_case
+    /// keys, so this keeps them rather than the camelCase of the wire form.
+    pub fn db_json(samples: &[Self]) -> serde_json::Value {
+        samples
+            .iter()
+            .map(|c| {
+                serde_json::json!({
+                    "id": c.id,
+                    "population": c.population,
+                    "food_stored": c.food_stored,
+                    "foragers": c.foragers,
+                    "deposit_rate": c.deposit_rate,
+                    "avg_trip_ticks": c.avg_trip_ticks,
+                    "eggs": c.eggs,
+                    "larvae": c.larvae,
+                    "deaths": {
+                        "starvation": c.deaths.starvation,
+                        "old_age": c.deaths.old_age,
+                        "hazard": c.deaths.hazard,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// One ant as returned by `GET /simulations/:id/ants`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AntSample {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub colony_id: u32,
+    pub role: &'static str,
+    pub returning: bool,
+    pub cargo: f32,
+    pub energy: f32,
+    pub health: f32,
+}
+
+impl AntSample {
+    /// Up to `limit` ants within `radius` of (`x`, `y`), nearest first.
+    pub fn capture_near(
+        sim: &SimulationState,
+        x: f32,
+        y: f32,
+        radius: f32,
+        limit: usize,
+    ) -> Vec<Self> {
+        let ants = &sim.ants;
+        sim.ants_near(x, y, radius)
+            .into_iter()
+            .take(limit)
+            .map(|i| Self {
+                id: ants.id[i],
+                x: ants.pos_x[i],
+                y: ants.pos_y[i],
+                colony_id: ants.colony_id[i],
+                role: role_name(ants.ant_type[i]),
+                returning: ants.state[i] == AntState::Returning,
+                cargo: ants.cargo[i],
+                energy: ants.energy[i],
+                health: ants.health[i],
+            })
+            .collect()
+    }
+}
+
+/// Samples the simulation every `every` ticks into a bounded ring buffer;
+/// the oldest samples are dropped once `capacity` is reached.
+pub struct StatsRecorder {
+    every: u64,
+    capacity: usize,
+    samples: VecDeque<StatsSample>,
+}
+
+impl StatsRecorder {
+    pub fn new(every: u64, capacity: usize) -> Self {
+        Self {
+            every: every.max(1),
+            capacity: capacity.max(1),
+            samples: VecDeque::with_capacity(capacity.min(4096)),
+        }
+    }
+
+    /// Record a sample if the current tick falls on the sampling interval.
+    pub fn maybe_record(&mut self, sim: &SimulationState) {
+        // a stopped simulation keeps its tick; don't sample it twice
+        if self
+            .samples
+            .back()
+            .is_some_and(|s| s.tick == sim.tick_count)
+        {
+            return;
+        }
+        if sim.tick_count.is_multiple_of(self.every) {
+            if self.samples.len() == self.capacity {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(StatsSample::capture(sim));
+        }
+    }
+
+    /// Drop all samples (e.g. when switching to another simulation, whose
+    /// tick counter restarts).
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn save_csv(&self, path: &Path) -> std::io::Result<()> {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(out, "{}", CSV_HEADER)?;
+        for s in &self.samples {
+            writeln!(out, "{}", s.csv_row())?;
+        }
+        out.flush()
+    }
+}
+
+const CSV_HEADER: &str = "tick,total_ants,active_colonies,total_food_collected,pheromone_count";
+
+impl StatsSample {
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.tick,
+            self.total_ants,
+            self.active_colonies,
+            self.total_food_collected,
+            self.pheromone_count
+        )
+    }
+}
+
+/// Write several recorders into one CSV, with a leading `sim` column
+/// holding each recorder's label.
+pub fn save_csv_multi(recorders: &[(String, StatsRecorder)], path: &Path) -> std::io::Result<()> {
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(out, "sim,{}", CSV_HEADER)?;
+    for (label, recorder) in recorders {
+        for s in &recorder.samples {
+            writeln!(out, "{},{}", label, s.csv_row())?;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimConfig;
+
+    #[test]
+    fn csv_has_header_and_increasing_ticks() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        let mut recorder = StatsRecorder::new(5, 100);
+        for _ in 0..40 {
+            sim.tick();
+            recorder.maybe_record(&sim);
+        }
+        let path = std::env::temp_dir().join(format!("ant-stats-{}.csv", std::process::id()));
+        recorder.save_csv(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let mut lines = text.lines();
+        assert_eq!(
+            lines.next(),
+            Some("tick,total_ants,active_colonies,total_food_collected,pheromone_count")
+        );
+        let ticks: Vec<u64> = lines
+            .map(|l| l.split(',').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(ticks, vec![5, 10, 15, 20, 25, 30, 35, 40]);
+    }
+
+    #[test]
+    fn sample_counts_living_ants_and_colonies() {
+        let mut sim = SimulationState::new(SimConfig {
+            colony_count: 3,
+            colony_spawn_interval: 1_000_000,
+            ..SimConfig::small()
+        });
+        // wipe out the middle colony
+        let gone = sim.colonies[1].id;
+        for i in (0..sim.ants.count).rev() {
+            if sim.ants.colony_id[i] == gone {
+                sim.ants.remove(i);
+            }
+        }
+        sim.tick();
+
+        let sample = StatsSample::capture(&sim);
+        assert_eq!(sample.tick, 1);
+        assert_eq!(sample.total_ants, sim.ants.count);
+        let living = sim
+            .colonies
+            .iter()
+            .filter(|c| sim.ants.colony_id[..sim.ants.count].contains(&c.id))
+            .count();
+        assert_eq!(living, 2);
+        assert_eq!(sample.active_colonies, living);
+    }
+
+    #[test]
+    fn ants_near_a_point_come_nearest_first() {
+        let mut sim = SimulationState::new(SimConfig::small());
+        for _ in 0..30 {
+            sim.tick();
+        }
+        let (x, y, radius) = (620.0, 440.0, 60.0);
+        let dist =
+            |i: usize| ((sim.ants.pos_x[i] - x).powi(2) + (sim.ants.pos_y[i] - y).powi(2)).sqrt();
+        let inside = (0..sim.ants.count).filter(|&i| dist(i) <= radius).count();
+        assert!(inside > 5, "only {inside} ants near the nest");
+
+        let near = sim.ants_near(x, y, radius);
+        assert_eq!(near.len(), inside);
+        assert!(near.windows(2).all(|w| dist(w[0]) <= dist(w[1])));
+
+        let samples = AntSample::capture_near(&sim, x, y, radius, 5);
+        assert_eq!(samples.len(), 5);
+        for (sample, &i) in samples.iter().zip(&near) {
+            assert_eq!(sample.id, sim.ants.id[i]);
+            assert_eq!((sample.x, sample.y), (sim.ants.pos_x[i], sim.ants.pos_y[i]));
+        }
+        assert!(AntSample::capture_near(&sim, 5.0, 5.0, 1.0, 5).is_empty());
+    }
+
+    #[test]
+    fn colony_stats_column_keeps_snake_case_keys() {
+        let sim = SimulationState::new(SimConfig::small());
+        let samples = ColonySample::capture_all(&sim);
+
+        let db = ColonySample::db_json(&samples);
+        let row = &db[0];
+        assert!(row["food_stored"].is_number());
+        assert!(row["avg_trip_ticks"].is_null());
+        assert!(row["deaths"]["old_age"].is_number());
+        assert!(row.get("foodStored").is_none());
+
+        let wire = serde_json::to_value(&samples).unwrap();
+        assert!(wire[0]["foodStored"].is_number());
+        assert!(wire[0]["deaths"]["oldAge"].is_number());
+    }
+}